@@ -0,0 +1,237 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Deserializer};
+
+use crate::device_io::parse_page_addr;
+
+// 芯片描述文件，位于程序工作目录；不存在时使用内置的描述
+pub const CHIP_DATABASE_FILE: &str = "chips.json";
+
+// 内置的芯片描述，与仓库中的 chips.json 相同
+const BUILTIN_CHIPS: &str = include_str!("../chips.json");
+
+lazy_static! {
+    static ref CHIP_DATABASE: RwLock<Arc<ChipDatabase>> =
+        RwLock::new(Arc::new(ChipDatabase::builtin()));
+}
+
+// 地址和数值可以写成 "0x4000" 形式的字符串，也可以直接写十进制数字
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HexValue {
+    Number(u16),
+    Text(String),
+}
+
+impl HexValue {
+    fn parse(self) -> anyhow::Result<u16> {
+        match self {
+            Self::Number(value) => Ok(value),
+            Self::Text(text) => parse_page_addr(text.trim()),
+        }
+    }
+}
+
+fn deserialize_hex<'de, D>(deserializer: D) -> Result<u16, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    HexValue::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+fn deserialize_hex_list<'de, D>(deserializer: D) -> Result<Vec<u16>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<HexValue>::deserialize(deserializer)?
+        .into_iter()
+        .map(|value| value.parse().map_err(serde::de::Error::custom))
+        .collect()
+}
+
+fn default_mask() -> u16 {
+    0xFFFF
+}
+
+// 版本号的解码方式: (寄存器值 & mask) >> shift
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RevisionField {
+    #[serde(deserialize_with = "deserialize_hex")]
+    pub register: u16,
+    #[serde(default = "default_mask", deserialize_with = "deserialize_hex")]
+    pub mask: u16,
+    #[serde(default)]
+    pub shift: u8,
+}
+
+impl RevisionField {
+    pub fn decode(&self, value: u16) -> u16 {
+        (value & self.mask) >> self.shift
+    }
+}
+
+// 一个芯片系列的描述
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ChipDescriptor {
+    // 芯片系列名称，例如 "MALD"
+    pub family: String,
+    // 芯片ID寄存器地址
+    #[serde(deserialize_with = "deserialize_hex")]
+    pub id_register: u16,
+    // 该系列可能读出的芯片ID
+    #[serde(deserialize_with = "deserialize_hex_list")]
+    pub id_values: Vec<u16>,
+    // 版本号寄存器及解码方式
+    #[serde(default)]
+    pub revision: Option<RevisionField>,
+    // IO1-IOn 的寄存器地址
+    #[serde(default, deserialize_with = "deserialize_hex_list")]
+    pub io_registers: Vec<u16>,
+    // 默认寄存器表 CSV，相对路径以描述文件所在目录为准
+    #[serde(default)]
+    pub register_map: Option<PathBuf>,
+}
+
+// 芯片描述库，新增芯片系列只需修改描述文件
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChipDatabase {
+    chips: Vec<ChipDescriptor>,
+}
+
+impl ChipDatabase {
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let chips: Vec<ChipDescriptor> = serde_json::from_str(json)
+            .map_err(|e| anyhow::anyhow!("芯片描述文件格式错误: {}", e))?;
+        Ok(Self { chips })
+    }
+
+    // 从描述文件加载，register_map 的相对路径换算为相对描述文件所在目录
+    pub fn load(file_path: &Path) -> anyhow::Result<Self> {
+        let json = std::fs::read_to_string(file_path)
+            .map_err(|e| anyhow::anyhow!("无法读取芯片描述文件 {:?}: {}", file_path, e))?;
+        let mut database = Self::from_json(&json)?;
+
+        let base_dir = file_path.parent().unwrap_or(Path::new(""));
+        for chip in &mut database.chips {
+            if let Some(register_map) = chip.register_map.as_mut()
+                && register_map.is_relative()
+            {
+                *register_map = base_dir.join(&*register_map);
+            }
+        }
+        Ok(database)
+    }
+
+    pub fn builtin() -> Self {
+        Self::from_json(BUILTIN_CHIPS).expect("内置芯片描述无效")
+    }
+
+    pub fn chips(&self) -> &[ChipDescriptor] {
+        &self.chips
+    }
+
+    // 根据ID寄存器地址和读到的值查找芯片系列
+    pub fn identify(&self, id_register: u16, value: u16) -> Option<&ChipDescriptor> {
+        self.chips
+            .iter()
+            .find(|chip| chip.id_register == id_register && chip.id_values.contains(&value))
+    }
+
+    pub fn find_family(&self, family: &str) -> Option<&ChipDescriptor> {
+        self.chips.iter().find(|chip| chip.family == family)
+    }
+}
+
+// 当前使用的芯片描述库
+pub fn chip_database() -> Arc<ChipDatabase> {
+    CHIP_DATABASE.read().unwrap().clone()
+}
+
+pub fn set_chip_database(database: ChipDatabase) {
+    *CHIP_DATABASE.write().unwrap() = Arc::new(database);
+}
+
+// 启动时加载描述文件；文件不存在或无效时保留内置描述
+pub fn init_chip_database(file_path: &Path) {
+    if !file_path.exists() {
+        log::info!("未找到芯片描述文件 {:?}，使用内置描述", file_path);
+        return;
+    }
+
+    match ChipDatabase::load(file_path) {
+        Ok(database) => {
+            log::info!(
+                "载入芯片描述文件 {:?}: {} 个芯片系列",
+                file_path,
+                database.chips().len()
+            );
+            set_chip_database(database);
+        }
+        Err(e) => log::error!("{}，使用内置描述", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_builtin_database() {
+        let database = ChipDatabase::builtin();
+        assert_eq!(database.identify(0x4000, 0x1D).unwrap().family, "MALD");
+        assert_eq!(database.identify(0xC000, 0x10).unwrap().family, "MATA");
+        // ID 值只在对应的ID寄存器上有效
+        assert!(database.identify(0xC000, 0x1C).is_none());
+        assert!(database.identify(0x4000, 0x55).is_none());
+
+        let mald = database.find_family("MALD").unwrap();
+        assert_eq!(mald.io_registers, vec![0x4001, 0x4002, 0x4003]);
+        // 版本号在芯片ID之后的 REVID 寄存器中
+        let revision = mald.revision.as_ref().unwrap();
+        assert_eq!(revision.register, 0x4001);
+        assert_eq!(revision.decode(0x05), 5);
+    }
+
+    #[test]
+    fn test_load_new_family() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(
+            file,
+            r#"[{{
+                "family": "MAXX",
+                "id_register": "0x8000",
+                "id_values": ["0x2A", 43],
+                "revision": {{ "register": "0x8001", "mask": "0xF0", "shift": 4 }},
+                "io_registers": ["0x8010", "0x8011"],
+                "register_map": "maxx.csv"
+            }}]"#
+        )
+        .unwrap();
+
+        let database = ChipDatabase::load(file.path()).unwrap();
+        let chip = database.identify(0x8000, 0x2B).unwrap();
+        assert_eq!(chip.family, "MAXX");
+        assert_eq!(chip.revision.as_ref().unwrap().decode(0x35), 3);
+        assert_eq!(chip.io_registers, vec![0x8010, 0x8011]);
+        // 寄存器表路径相对描述文件所在目录
+        assert_eq!(
+            chip.register_map.as_deref(),
+            Some(file.path().parent().unwrap().join("maxx.csv").as_path())
+        );
+    }
+
+    #[test]
+    fn test_invalid_descriptor() {
+        assert!(
+            ChipDatabase::from_json(r#"[{"family": "X", "id_register": "0xZZ", "id_values": []}]"#)
+                .is_err()
+        );
+        assert!(ChipDatabase::from_json(r#"[{"family": "X"}]"#).is_err());
+    }
+}
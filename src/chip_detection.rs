@@ -1,109 +1,345 @@
 use std::error::Error;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
 use std::sync::Arc;
 
-use crate::serial::base::SerialPortManager;
-use crate::serial::modbus::{ModbusFrame, RegisterType};
+use crate::chip_db::chip_database;
+use crate::device_io::io_registers;
+use crate::serial::modbus::{ModbusError, ModbusFrame, ModbusTransaction, RegisterType};
+use crate::serial::transport::ModbusTransport;
 
-// 芯片类型枚举
+// Modbus 从站地址范围 (1-247)
+pub const SLAVE_ADDRESS_RANGE: RangeInclusive<u8> = 1..=247;
+// 总线扫描时每个地址等待应答的时间，毫秒
+pub const SCAN_TIMEOUT_MS: u64 = 100;
+
+// 芯片类型，已识别的芯片系列来自芯片描述库
 #[derive(Debug, Clone, PartialEq)]
 pub enum ChipType {
-    MALD,
-    MATA,
+    Family(String),
     Unknown,
 }
 
 impl ChipType {
-    pub fn to_string(&self) -> String {
+    pub fn family(name: &str) -> Self {
+        Self::Family(name.to_string())
+    }
+
+    // 芯片的 IO 寄存器地址，未识别的芯片或描述中没有 IO 时使用 default_base 起的 IO1-IO3
+    pub fn io_registers(&self, default_base: u16) -> Vec<u16> {
+        let registers = match self {
+            Self::Family(name) => chip_database()
+                .find_family(name)
+                .map(|chip| chip.io_registers.clone())
+                .unwrap_or_default(),
+            Self::Unknown => Vec::new(),
+        };
+        if registers.is_empty() {
+            io_registers(default_base)
+        } else {
+            registers
+        }
+    }
+
+    // 芯片描述中的默认寄存器表
+    pub fn register_map(&self) -> Option<PathBuf> {
         match self {
-            Self::MALD => "MALD".to_string(),
-            Self::MATA => "MATA".to_string(),
-            Self::Unknown => "未知".to_string(),
+            Self::Family(name) => chip_database()
+                .find_family(name)
+                .and_then(|chip| chip.register_map.clone()),
+            Self::Unknown => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ChipType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Family(name) => write!(f, "{}", name),
+            Self::Unknown => write!(f, "未知"),
+        }
+    }
+}
+
+// 芯片检测读取的超时时间，毫秒
+pub const DETECT_TIMEOUT_MS: u64 = 1000;
+
+// 检测得到的芯片身份
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChipIdentity {
+    // 芯片系列，ID 不在芯片描述库中时为 Unknown
+    pub chip_type: ChipType,
+    // 读到的原始芯片ID，从站返回异常响应时为 None
+    pub chip_id: Option<u16>,
+    // 按芯片描述解码的版本号
+    pub revision: Option<u16>,
+    // 芯片所在页的基地址 (芯片ID寄存器地址)
+    pub page_base: u16,
+}
+
+impl ChipIdentity {
+    pub fn unknown(page_base: u16) -> Self {
+        Self {
+            chip_type: ChipType::Unknown,
+            chip_id: None,
+            revision: None,
+            page_base,
+        }
+    }
+}
+
+// 界面显示，例如 "MALD rev 5"
+impl std::fmt::Display for ChipIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.chip_type, self.revision, self.chip_id) {
+            (ChipType::Family(name), Some(revision), _) => write!(f, "{} rev {}", name, revision),
+            (ChipType::Family(name), None, _) => write!(f, "{}", name),
+            (ChipType::Unknown, _, Some(chip_id)) => write!(f, "未知 (ID 0x{:02X})", chip_id),
+            (ChipType::Unknown, _, None) => write!(f, "未知"),
         }
     }
 }
 
 // 异步芯片检测函数
 pub async fn detect_chip_type(
-    port_manager: Arc<SerialPortManager>,
+    port_manager: Arc<dyn ModbusTransport>,
     slave_address: u8,
     page40_reg0_address: u16,
-) -> Result<ChipType, Box<dyn Error + Send + Sync>> {
-    // 构建 Modbus RTU 读取命令
-    let frame = ModbusFrame::new_read_request(
+) -> Result<ChipIdentity, Box<dyn Error + Send + Sync>> {
+    Ok(read_chip_identity(
+        port_manager.as_ref(),
+        slave_address,
+        page40_reg0_address,
+        DETECT_TIMEOUT_MS,
+    )
+    .await?)
+}
+
+// 读取芯片ID，在芯片描述库中识别芯片系列，再按描述读取并解码版本号
+// 版本号读取失败不影响芯片识别
+pub async fn read_chip_identity(
+    port_manager: &dyn ModbusTransport,
+    slave_address: u8,
+    page_base: u16,
+    timeout_ms: u64,
+) -> anyhow::Result<ChipIdentity> {
+    let chip_id = read_chip_id(port_manager, slave_address, page_base, timeout_ms).await?;
+    let database = chip_database();
+    let descriptor = chip_id.and_then(|value| database.identify(page_base, value));
+
+    let revision = match descriptor.and_then(|chip| chip.revision.as_ref()) {
+        Some(field) => {
+            match read_chip_id(port_manager, slave_address, field.register, timeout_ms).await {
+                Ok(value) => value.map(|value| field.decode(value)),
+                Err(e) => {
+                    log::warn!("读取版本号寄存器 0x{:04X} 失败: {}", field.register, e);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    Ok(ChipIdentity {
+        chip_type: descriptor.map_or(ChipType::Unknown, |chip| ChipType::family(&chip.family)),
+        chip_id,
+        revision,
+        page_base,
+    })
+}
+
+// 以指定超时读取一个芯片ID寄存器
+// 从站返回异常响应时返回 Ok(None)，说明从站存在但该地址不可读
+pub async fn read_chip_id(
+    port_manager: &dyn ModbusTransport,
+    slave_address: u8,
+    page40_reg0_address: u16,
+    timeout_ms: u64,
+) -> anyhow::Result<Option<u16>> {
+    let request = ModbusFrame::new_read_request(
         slave_address,
         RegisterType::HoldingRegister,
         page40_reg0_address,
-        1, // 读取1个寄存器
+        1,
     )?;
 
-    let command = frame.to_bytes();
-    // log::info!("发送芯片检测命令: {:02X?}", command);
+    match port_manager
+        .transact(ModbusTransaction::new(request), timeout_ms)
+        .await
+    {
+        Ok(response) => Ok(response.get_registers()?.first().copied()),
+        Err(e)
+            if e.downcast_ref::<ModbusError>()
+                .and_then(ModbusError::exception)
+                .is_some() =>
+        {
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// 总线上一个有应答的从站
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlaveScanResult {
+    pub slave_address: u8,
+    pub chip1: ChipIdentity,
+    pub chip2: ChipIdentity,
+}
 
-    // 发送命令并接收响应
-    let response = port_manager.send_modbus_command(&command, 1000).await?;
-    // log::info!("接收到响应: {:02X?}", response);
+impl std::fmt::Display for SlaveScanResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "从站 {}: 芯片1 {}, 芯片2 {}",
+            self.slave_address, self.chip1, self.chip2
+        )
+    }
+}
 
-    // 解析响应
-    let response_frame = ModbusFrame::from_bytes(&response)?;
+// 扫描多点总线上的从站地址
+// 对每个地址读取 0x4000，有应答 (包括异常响应) 即视为从站存在，再读取 0xC000 判断芯片二；
+// progress(当前地址, 已发现的从站数)
+pub async fn scan_bus<F>(
+    port_manager: Arc<dyn ModbusTransport>,
+    addresses: RangeInclusive<u8>,
+    timeout_ms: u64,
+    mut progress: F,
+) -> Vec<SlaveScanResult>
+where
+    F: FnMut(u8, usize) + Send,
+{
+    let mut found = Vec::new();
 
-    let data = response_frame.get_data();
-    if data.len() >= 2 {
-        let value = u16::from_be_bytes([data[1], data[2]]);
-        log::info!("读取到寄存器值: 0x{:04X}", value);
+    for slave_address in addresses {
+        progress(slave_address, found.len());
 
-        // 根据地址和值判断芯片类型
-        match page40_reg0_address {
-            0x4000 => {
-                // 芯片1
-                match value {
-                    0x1C | 0x1D => Ok(ChipType::MALD),
-                    _ => Ok(ChipType::Unknown),
-                }
-            }
-            0xC000 => {
-                // 芯片2
-                match value {
-                    0x10 | 0x11 => Ok(ChipType::MATA),
-                    _ => Ok(ChipType::Unknown),
-                }
+        let chip1 = match read_chip_identity(
+            port_manager.as_ref(),
+            slave_address,
+            0x4000,
+            timeout_ms,
+        )
+        .await
+        {
+            Ok(identity) => identity,
+            Err(e) => {
+                log::debug!("从站 {} 无应答: {}", slave_address, e);
+                continue;
             }
-            _ => Ok(ChipType::Unknown),
-        }
-    } else {
-        log::warn!("响应数据长度不足");
-        Ok(ChipType::Unknown)
+        };
+        let chip2 = read_chip_identity(port_manager.as_ref(), slave_address, 0xC000, timeout_ms)
+            .await
+            .unwrap_or_else(|e| {
+                log::warn!("从站 {} 芯片2检测失败: {}", slave_address, e);
+                ChipIdentity::unknown(0xC000)
+            });
+
+        let result = SlaveScanResult {
+            slave_address,
+            chip1,
+            chip2,
+        };
+        log::info!("发现{}", result);
+        found.push(result);
     }
-}
 
-// 检测两个芯片的类型
-pub async fn detect_all_chips(port_manager: Arc<SerialPortManager>) -> (ChipType, ChipType) {
-    let mut chip1_type = ChipType::Unknown;
-    let mut chip2_type = ChipType::Unknown;
+    found
+}
 
+// 检测两个芯片的身份
+pub async fn detect_all_chips(
+    port_manager: Arc<dyn ModbusTransport>,
+) -> (ChipIdentity, ChipIdentity) {
     // 检测芯片1 (地址 0x4000)
-    match detect_chip_type(port_manager.clone(), 0x01, 0x4000).await {
-        Ok(chip_type) => {
-            chip1_type = chip_type;
-            log::info!("芯片1检测结果: {:?}", chip1_type);
-        }
-        Err(e) => {
-            log::error!("芯片1检测失败: {}", e);
-        }
-    }
+    let chip1 = detect_and_log(port_manager.clone(), 1, 0x4000).await;
 
     // 等待一段时间再检测芯片2
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
     // 检测芯片2 (地址 0xC000)
-    match detect_chip_type(port_manager.clone(), 0x01, 0xC000).await {
-        Ok(chip_type) => {
-            chip2_type = chip_type;
-            log::info!("芯片2检测结果: {:?}", chip2_type);
+    let chip2 = detect_and_log(port_manager, 2, 0xC000).await;
+
+    (chip1, chip2)
+}
+
+// 检测一个芯片并在日志中记录完整身份，便于追溯
+async fn detect_and_log(
+    port_manager: Arc<dyn ModbusTransport>,
+    index: u8,
+    page_base: u16,
+) -> ChipIdentity {
+    match detect_chip_type(port_manager, 0x01, page_base).await {
+        Ok(identity) => {
+            log::info!(
+                "芯片{}检测结果: {} (系列={}, 芯片ID={}, 版本={}, 页基地址=0x{:04X})",
+                index,
+                identity,
+                identity.chip_type,
+                identity
+                    .chip_id
+                    .map_or("无".to_string(), |id| format!("0x{:04X}", id)),
+                identity
+                    .revision
+                    .map_or("无".to_string(), |revision| revision.to_string()),
+                identity.page_base
+            );
+            identity
         }
         Err(e) => {
-            log::error!("芯片2检测失败: {}", e);
+            log::error!("芯片{}检测失败: {}", index, e);
+            ChipIdentity::unknown(page_base)
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::mock::{MockBus, MockTransport};
 
-    (chip1_type, chip2_type)
+    #[tokio::test]
+    async fn test_scan_bus() {
+        // 从站1: MALD + MATA，从站5: 只有 MALD，从站9: ID寄存器不可读
+        let bus = Arc::new(MockBus::new(
+            "bus",
+            [
+                MockTransport::new("slave1", 1).with_registers([
+                    (0x4000, 0x1C),
+                    (0x4001, 0x02),
+                    (0xC000, 0x10),
+                    (0xC001, 0x01),
+                ]),
+                // 没有 REVID 寄存器时只显示芯片系列
+                MockTransport::new("slave5", 5).with_registers([(0x4000, 0x1D)]),
+                MockTransport::new("slave9", 9),
+            ],
+        ));
+        bus.open().await.unwrap();
+
+        let mut scanned = Vec::new();
+        let found = scan_bus(bus, 1..=10, SCAN_TIMEOUT_MS, |address, _| {
+            scanned.push(address)
+        })
+        .await;
+
+        assert_eq!(scanned, (1..=10).collect::<Vec<u8>>());
+        let summary: Vec<String> = found.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            summary,
+            vec![
+                "从站 1: 芯片1 MALD rev 2, 芯片2 MATA rev 1",
+                "从站 5: 芯片1 MALD, 芯片2 未知",
+                "从站 9: 芯片1 未知, 芯片2 未知",
+            ]
+        );
+        assert_eq!(found[1].chip1.chip_id, Some(0x1D));
+        assert_eq!(found[1].chip2.page_base, 0xC000);
+
+        // 原始ID不在描述库中时显示ID
+        let bus = Arc::new(MockTransport::new("slave1", 1).with_registers([(0x4000, 0x55)]));
+        bus.open().await.unwrap();
+        let found = scan_bus(bus, 1..=1, SCAN_TIMEOUT_MS, |_, _| {}).await;
+        assert_eq!(found[0].chip1.to_string(), "未知 (ID 0x55)");
+    }
 }
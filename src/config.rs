@@ -46,18 +46,19 @@ pub fn init_config() {
     init_log();
 }
 
-pub fn write_vec_to_file(filename: &str, data: &Vec<String>) {
-    let mut file = File::create(filename).unwrap();
+pub fn write_vec_to_file(filename: &str, data: &[String]) -> anyhow::Result<()> {
+    let mut file = File::create(filename)?;
 
     for item in data {
         // 写入文件
-        file.write_all(item.as_bytes()).unwrap();
+        file.write_all(item.as_bytes())?;
     }
 
     log::info!("save content to file = {}", filename);
+    Ok(())
 }
 
 
 pub fn get_runtime() -> Arc<Runtime> {
-    return RUNTIME.clone();
+    RUNTIME.clone()
 }
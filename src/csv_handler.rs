@@ -2,11 +2,57 @@ use anyhow::{Result, anyhow};
 use lazy_static::lazy_static;
 use rfd::FileDialog;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use tokio::sync::Mutex;
 
+use crate::device_io::parse_page_addr;
+use crate::register_map::{RegisterDef, parse_number};
+use crate::register_store::{RegisterKey, RegisterStore};
+
+/// 寄存器行在最近一次器件读写中的状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RowStatus {
+    /// 未参与读写
+    #[default]
+    Idle,
+    /// 等待读写
+    Pending,
+    /// 已从器件读取
+    Read,
+    /// 已写入器件
+    Written,
+    /// 预览中当前值与设置值相同，跳过写入
+    Unchanged,
+    /// 任务停止或出错时尚未完成
+    Unfinished,
+    /// 回读值与写入值一致
+    Verified,
+    /// 回读值与写入值不一致
+    Mismatch { actual: u32 },
+    /// 回读失败
+    VerifyFailed,
+    /// 只写寄存器，无法回读校验
+    NotVerifiable,
+}
+
+impl std::fmt::Display for RowStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Idle => write!(f, ""),
+            Self::Pending => write!(f, "等待"),
+            Self::Read => write!(f, "已读取"),
+            Self::Written => write!(f, "已写入"),
+            Self::Unchanged => write!(f, "无变化"),
+            Self::Unfinished => write!(f, "未完成"),
+            Self::Verified => write!(f, "校验通过"),
+            Self::Mismatch { actual } => write!(f, "不一致 (读回 0x{:02X})", actual),
+            Self::VerifyFailed => write!(f, "校验失败"),
+            Self::NotVerifiable => write!(f, "只写未校验"),
+        }
+    }
+}
+
 /// CSV文件中的寄存器记录
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RegisterRecord {
     #[serde(rename = "Page_Addr")]
     pub page_addr: String,
@@ -16,20 +62,77 @@ pub struct RegisterRecord {
     pub r_w: String,
     #[serde(rename = "Value")]
     pub value: String,
+    /// 回读校验时比较的位，可选列，为空时比较可写的位
+    #[serde(rename = "Mask", default)]
+    pub mask: Option<String>,
+    /// 寄存器宽度 8/16/32，可选列，为空时为 8 位
+    #[serde(rename = "Width", default)]
+    pub width: Option<String>,
+    /// 复位值，可选列
+    #[serde(rename = "Reset", default)]
+    pub reset: Option<String>,
+    /// 可写的位，可选列，为空时全部位可写
+    #[serde(rename = "Write_Mask", default)]
+    pub write_mask: Option<String>,
+    /// 位域定义，可选列，例如 "EN[0];MODE[2:1]=SLOW:0|FAST:1"
+    #[serde(rename = "Fields", default)]
+    pub fields: Option<String>,
+    /// 由以上各列解析得到的寄存器定义
+    #[serde(skip)]
+    pub def: RegisterDef,
     /// 额外的写入值字段，默认为空
     #[serde(skip)]
     pub w_value: Option<String>,
+    /// 最近一次器件读写的状态
+    #[serde(skip)]
+    pub status: RowStatus,
 }
 
 impl RegisterRecord {
-    /// 创建新的寄存器记录
+    /// 创建新的寄存器记录，可选列为空
     pub fn new(page_addr: String, register: String, r_w: String, value: String) -> Self {
-        Self {
+        let mut record = Self {
             page_addr,
             register,
             r_w,
             value,
+            mask: None,
+            width: None,
+            reset: None,
+            write_mask: None,
+            fields: None,
+            def: RegisterDef::default(),
             w_value: None,
+            status: RowStatus::Idle,
+        };
+        match record.parse_def() {
+            Ok(def) => record.def = def,
+            Err(e) => log::warn!("寄存器 {} 定义无效: {}", record.register, e),
+        }
+        record
+    }
+
+    /// 解析寄存器定义
+    pub fn parse_def(&self) -> Result<RegisterDef> {
+        RegisterDef::parse(
+            &self.page_addr,
+            &self.register,
+            &self.r_w,
+            self.width.as_deref(),
+            self.reset.as_deref(),
+            self.write_mask.as_deref(),
+            self.fields.as_deref(),
+        )
+    }
+
+    /// 实时值及其位域解释，例如 "0x03 (EN=1, MODE=FAST)"
+    pub fn w_value_display(&self) -> String {
+        let Some(w_value) = self.w_value.as_deref() else {
+            return String::new();
+        };
+        match parse_number(w_value).map(|value| self.def.describe(value)) {
+            Ok(fields) if !fields.is_empty() => format!("{} ({})", w_value, fields),
+            _ => w_value.to_string(),
         }
     }
 
@@ -52,8 +155,7 @@ impl RegisterRecord {
 }
 
 lazy_static! {
-    pub static ref REGISTER_DATA: Mutex<HashMap<String, RegisterRecord>> =
-        Mutex::new(HashMap::new());
+    pub static ref REGISTER_DATA: Mutex<RegisterStore> = Mutex::new(RegisterStore::default());
 }
 /// CSV文件处理器
 pub struct CsvHandler;
@@ -67,13 +169,17 @@ impl CsvHandler {
             .pick_file()
     }
 
-    /// 解析CSV文件
+    /// 解析CSV文件，每行解析为寄存器定义，定义无效时报告行号
     pub fn parse_csv_file(file_path: &std::path::Path) -> Result<Vec<RegisterRecord>> {
         let mut reader = csv::Reader::from_path(file_path)?;
         let mut records = Vec::new();
 
-        for result in reader.deserialize() {
-            let record: RegisterRecord = result?;
+        for (index, result) in reader.deserialize().enumerate() {
+            let mut record: RegisterRecord = result?;
+            // 表头占第 1 行
+            record.def = record
+                .parse_def()
+                .map_err(|e| anyhow!("第 {} 行: {}", index + 2, e))?;
             records.push(record);
         }
 
@@ -87,10 +193,14 @@ impl CsvHandler {
         // 清空现有数据
         global_data.clear();
 
-        // 一对一存储，如果存在则覆盖
+        // 按地址 + 名称存储，同一地址可以有多行，地址和名称都相同时后一行覆盖前一行
         for record in records {
-            let key = format!("{}", record.page_addr);
-            global_data.insert(key, record);
+            if let Some(replaced) = global_data.insert(record) {
+                log::warn!(
+                    "寄存器 {} 重复，使用后出现的一行",
+                    RegisterKey::of(&replaced)
+                );
+            }
         }
 
         Ok(())
@@ -110,18 +220,13 @@ impl CsvHandler {
         table.push_str("页地址\t\t寄存器\t\t读写\t\t值\t\t写入值\n");
         table.push_str("-----------------------------------------------------------\n");
 
-        // 按key排序，然后显示记录
-        let mut sorted_keys: Vec<_> = global_data.keys().collect();
-        sorted_keys.sort();
-
-        for key in sorted_keys {
-            if let Some(record) = global_data.get(key) {
-                let w_value = record.w_value.as_deref().unwrap_or("");
-                table.push_str(&format!(
-                    "{}\t\t{}\t\t{}\t\t{}\t\t{}\n",
-                    record.page_addr, record.register, record.r_w, record.value, w_value
-                ));
-            }
+        // 按地址顺序显示记录
+        for record in global_data.records() {
+            let w_value = record.w_value.as_deref().unwrap_or("");
+            table.push_str(&format!(
+                "{}\t\t{}\t\t{}\t\t{}\t\t{}\n",
+                record.page_addr, record.register, record.r_w, record.value, w_value
+            ));
         }
 
         Ok(table)
@@ -131,45 +236,42 @@ impl CsvHandler {
     pub async fn get_slint_table_data() -> Result<Vec<Vec<slint::SharedString>>> {
         let global_data = REGISTER_DATA.lock().await;
 
-        if global_data.is_empty() {
-            return Ok(vec![]);
-        }
-
-        let mut table_data = Vec::new();
-
-        // 按key排序
-        let mut sorted_keys: Vec<_> = global_data.keys().collect();
-        sorted_keys.sort();
-
-        for key in sorted_keys {
-            if let Some(record) = global_data.get(key) {
-                let w_value = record.w_value.as_deref().unwrap_or("");
-                let row = vec![
+        // 按地址顺序
+        let table_data = global_data
+            .records()
+            .map(|record| {
+                vec![
                     record.page_addr.clone().into(),
                     record.register.clone().into(),
                     record.r_w.clone().into(),
                     record.value.clone().into(),
-                    w_value.to_string().into(),
-                ];
-                table_data.push(row);
-            }
-        }
+                    record.w_value_display().into(),
+                    record.status.to_string().into(),
+                ]
+            })
+            .collect();
 
         Ok(table_data)
     }
 
-    /// 根据页地址获取寄存器记录
-    pub async fn get_records_by_page(page_addr: &str) -> Result<Vec<RegisterRecord>> {
+    /// 获取一个地址上的所有寄存器行，按名称排序
+    pub async fn get_register_rows(address: u16) -> Vec<RegisterRecord> {
         let global_data = REGISTER_DATA.lock().await;
+        global_data.rows_at(address).cloned().collect()
+    }
 
-        let mut records = Vec::new();
-        for (_key, record) in global_data.iter() {
-            if record.page_addr == page_addr {
-                records.push(record.clone());
-            }
-        }
+    /// 按地址和名称获取一行寄存器记录
+    pub async fn get_register(address: u16, register: &str) -> Option<RegisterRecord> {
+        let global_data = REGISTER_DATA.lock().await;
+        global_data
+            .get(&RegisterKey::new(address, register))
+            .cloned()
+    }
 
-        Ok(records)
+    /// 根据页地址获取寄存器记录，页地址按数值比较
+    pub async fn get_records_by_page(page_addr: &str) -> Result<Vec<RegisterRecord>> {
+        let address = parse_page_addr(page_addr)?;
+        Ok(Self::get_register_rows(address).await)
     }
 
     /// 更新寄存器的写入值
@@ -180,7 +282,7 @@ impl CsvHandler {
     ) -> Result<()> {
         let mut global_data = REGISTER_DATA.lock().await;
 
-        let key = format!("{}", page_addr);
+        let key = RegisterKey::new(parse_page_addr(page_addr)?, register);
         if let Some(record) = global_data.get_mut(&key) {
             record.w_value = w_value;
             Ok(())
@@ -189,29 +291,56 @@ impl CsvHandler {
         }
     }
 
-    /// 获取所有寄存器记录
-    pub async fn get_all_records() -> Result<Vec<RegisterRecord>> {
-        let global_data = REGISTER_DATA.lock().await;
+    /// 设置一批寄存器行的读写状态
+    pub async fn set_status(records: &[RegisterRecord], status: RowStatus) {
+        let mut global_data = REGISTER_DATA.lock().await;
+
+        for record in records {
+            if let Some(existing) = global_data.get_mut(&RegisterKey::of(record)) {
+                existing.status = status;
+            }
+        }
+    }
 
-        let mut records: Vec<RegisterRecord> = global_data.values().cloned().collect();
+    /// 分别设置寄存器行的状态
+    pub async fn set_statuses(statuses: Vec<(RegisterKey, RowStatus)>) {
+        let mut global_data = REGISTER_DATA.lock().await;
+
+        for (key, status) in statuses {
+            if let Some(existing) = global_data.get_mut(&key) {
+                existing.status = status;
+            }
+        }
+    }
 
-        // 根据 page_addr 排序
-        records.sort_by_key(|record| record.page_addr.clone());
+    /// 任务结束时把仍在等待的行标记为未完成
+    pub async fn mark_pending_unfinished() {
+        let mut global_data = REGISTER_DATA.lock().await;
 
-        Ok(records)
+        for record in global_data.records_mut() {
+            if record.status == RowStatus::Pending {
+                record.status = RowStatus::Unfinished;
+            }
+        }
+    }
+
+    /// 获取所有寄存器记录，按地址排序
+    pub async fn get_all_records() -> Result<Vec<RegisterRecord>> {
+        let global_data = REGISTER_DATA.lock().await;
+
+        Ok(global_data.records().cloned().collect())
     }
 
-    /// 获取所有页地址
+    /// 获取所有页地址，按地址排序
     pub async fn get_all_page_addresses() -> Result<Vec<String>> {
         let global_data = REGISTER_DATA.lock().await;
 
-        let mut pages: Vec<String> = global_data
-            .values()
-            .map(|record| record.page_addr.clone())
-            .collect::<std::collections::HashSet<_>>()
+        let pages = global_data
+            .addresses()
             .into_iter()
+            .filter_map(|address| global_data.rows_at(address).next())
+            .map(|record| record.page_addr.clone())
             .collect();
-        pages.sort();
         Ok(pages)
     }
 
@@ -230,8 +359,13 @@ impl CsvHandler {
 
         log::info!("选择的文件: {:?}", file_path);
 
+        Self::load_csv_path(&file_path).await
+    }
+
+    /// 读取指定路径的CSV文件，存储到全局变量并返回表格字符串
+    pub async fn load_csv_path(file_path: &std::path::Path) -> Result<String> {
         // 2. 解析CSV文件
-        let records = Self::parse_csv_file(&file_path)?;
+        let records = Self::parse_csv_file(file_path)?;
         log::info!("解析到 {} 条记录", records.len());
 
         // 3. 存储到全局变量
@@ -269,6 +403,36 @@ mod tests {
         assert_eq!(records[0].w_value, None);
     }
 
+    #[test]
+    fn test_parse_typed_columns() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(
+            temp_file,
+            "Page_Addr,Register,R_W,Value,Width,Reset,Write_Mask,Fields"
+        )
+        .unwrap();
+        writeln!(
+            temp_file,
+            "0x4010,CTRL,RW,0x0102,16,0x0100,0x0FFF,EN[0];MODE[2:1]=SLOW:0|FAST:1"
+        )
+        .unwrap();
+        writeln!(temp_file, "0x4020,COUNTER,R,,32,,,").unwrap();
+
+        let records = CsvHandler::parse_csv_file(temp_file.path()).unwrap();
+        assert_eq!(records[0].def.write_mask, 0x0FFF);
+        assert_eq!(records[0].def.reset, Some(0x0100));
+        assert_eq!(records[1].def.addresses(), vec![0x4020, 0x4021]);
+
+        let mut record = records[0].clone();
+        record.w_value = Some(record.def.format_value(0x0003));
+        assert_eq!(record.w_value_display(), "0x0003 (EN=1, MODE=FAST)");
+
+        // 定义无效时报告行号
+        writeln!(temp_file, "0x4030,BAD,RW,0x00,12,,,").unwrap();
+        let error = CsvHandler::parse_csv_file(temp_file.path()).unwrap_err();
+        assert!(error.to_string().starts_with("第 4 行"));
+    }
+
     #[tokio::test]
     async fn test_store_and_retrieve() {
         // 清空数据
@@ -0,0 +1,1154 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::csv_handler::RegisterRecord;
+use crate::serial::modbus::{MAX_READ_REGISTERS, MAX_WRITE_REGISTERS, group_register_blocks};
+use crate::serial::retry::{Retried, RetryPolicy, with_retry};
+use crate::serial::transport::ModbusTransport;
+
+// 器件的 Modbus 从站地址
+pub const DEVICE_SLAVE_ADDRESS: u8 = 0x01;
+
+// 芯片一、芯片二 IO1-IO3 的起始寄存器地址
+pub const CHIP1_IO_BASE: u16 = 0x4001;
+pub const CHIP2_IO_BASE: u16 = 0xC001;
+pub const IO_COUNT: u16 = 3;
+
+// 从 io_base 起连续的 IO1-IO3 寄存器地址
+pub fn io_registers(io_base: u16) -> Vec<u16> {
+    (0..IO_COUNT).map(|offset| io_base + offset).collect()
+}
+
+// 解析页地址（十六进制 0x 前缀或十进制）
+pub fn parse_page_addr(page_addr: &str) -> anyhow::Result<u16> {
+    if page_addr.starts_with("0x") || page_addr.starts_with("0X") {
+        u16::from_str_radix(&page_addr[2..], 16)
+            .map_err(|_| anyhow::anyhow!("无效的页地址格式: {}", page_addr))
+    } else {
+        page_addr
+            .parse::<u16>()
+            .map_err(|_| anyhow::anyhow!("无效的页地址: {}", page_addr))
+    }
+}
+
+// 解析寄存器值（十六进制 0x 前缀或十进制）
+pub fn parse_register_value(value: &str) -> anyhow::Result<u16> {
+    if value.starts_with("0x") || value.starts_with("0X") {
+        u16::from_str_radix(&value[2..], 16).map_err(|_| anyhow::anyhow!("无效的值格式: {}", value))
+    } else {
+        value
+            .parse::<u16>()
+            .map_err(|_| anyhow::anyhow!("无效的值: {}", value))
+    }
+}
+
+// 标记为可读的寄存器
+pub fn is_readable(record: &RegisterRecord) -> bool {
+    record.def.access.is_readable()
+}
+
+// 标记为可写 (RW 或 W) 的寄存器
+pub fn is_writable(record: &RegisterRecord) -> bool {
+    record.def.access.is_writable()
+}
+
+// 寄存器的校验掩码，CSV 中没有 Mask 列或为空时比较可写的位
+pub fn register_mask(record: &RegisterRecord) -> anyhow::Result<u32> {
+    match record.mask.as_deref().map(str::trim) {
+        Some(mask) if !mask.is_empty() => record.def.parse_value(mask),
+        _ => Ok(record.def.write_mask),
+    }
+}
+
+// 批量读写被停止时返回的错误，done / total 为已完成和总的寄存器数
+fn cancelled_error(done: usize, total: usize) -> anyhow::Error {
+    anyhow::anyhow!("已停止，完成 {}/{} 个寄存器", done, total)
+}
+
+// 每个 Modbus 地址所属记录的下标，32 位寄存器占两个地址，
+// 同一地址可以属于多行 (例如同一寄存器的不同位域)
+fn word_owners<'a>(
+    records: impl IntoIterator<Item = &'a RegisterRecord>,
+) -> BTreeMap<u16, Vec<usize>> {
+    let mut owners: BTreeMap<u16, Vec<usize>> = BTreeMap::new();
+    for (index, record) in records.into_iter().enumerate() {
+        for address in record.def.addresses() {
+            owners.entry(address).or_default().push(index);
+        }
+    }
+    owners
+}
+
+// 是否为寄存器占用的最后一个地址，读写到这里时该寄存器完成
+fn is_last_word(record: &RegisterRecord, address: u16) -> bool {
+    record.def.addresses().last() == Some(&address)
+}
+
+// 读取所有可读寄存器，返回填好 w_value 的记录及所有块合计的重试次数
+// 连续地址合并为块读取，每块最多125个寄存器，按 policy 重试；
+// 每块开始前检查 cancel，已停止时返回错误；
+// progress(当前块序号, 总块数, 块地址范围)，completed(本块读完的记录)
+pub async fn read_device_registers<F, C>(
+    port_manager: Arc<dyn ModbusTransport>,
+    records: &[RegisterRecord],
+    policy: &RetryPolicy,
+    cancel: &CancellationToken,
+    mut progress: F,
+    mut completed: C,
+) -> anyhow::Result<Retried<Vec<RegisterRecord>>>
+where
+    F: FnMut(usize, usize, &str) + Send,
+    C: FnMut(&[RegisterRecord]) + Send,
+{
+    // 只读取标记为可读的寄存器
+    let mut readable: Vec<RegisterRecord> = records
+        .iter()
+        .filter(|record| is_readable(record))
+        .cloned()
+        .collect();
+    let owners = word_owners(&readable);
+    // 各寄存器已读到的字，32 位寄存器可能跨两个块
+    let mut words = vec![Vec::new(); readable.len()];
+
+    let addresses: Vec<u16> = owners.keys().copied().collect();
+    let blocks = group_register_blocks(&addresses, MAX_READ_REGISTERS);
+    let total_blocks = blocks.len();
+    let mut retries = 0;
+    let mut done = 0;
+
+    for (index, (start, count)) in blocks.into_iter().enumerate() {
+        if cancel.is_cancelled() {
+            return Err(cancelled_error(done, readable.len()));
+        }
+
+        let block_label = format!("0x{:04X}-0x{:04X}", start, start + (count - 1));
+        progress(index, total_blocks, &block_label);
+
+        let values = match with_retry(policy, || {
+            port_manager.read_registers(DEVICE_SLAVE_ADDRESS, start, count)
+        })
+        .await
+        {
+            Ok(read) => {
+                retries += read.retries;
+                read.value
+            }
+            Err(e) => {
+                log::warn!("读取寄存器块失败 {} - {:#}", block_label, e);
+                return Err(anyhow::anyhow!(
+                    "读取寄存器块失败 {} - {:#}",
+                    block_label,
+                    e
+                ));
+            }
+        };
+
+        let mut block_records = Vec::new();
+        for (address, value) in (start..).zip(values) {
+            for &owner in &owners[&address] {
+                words[owner].push(value);
+                let record = &mut readable[owner];
+                if !is_last_word(record, address) {
+                    continue;
+                }
+
+                let hex_value = record
+                    .def
+                    .format_value(record.def.join_words(&words[owner]));
+                log::info!(
+                    "读取寄存器成功: {}:{} = {}",
+                    record.page_addr,
+                    record.register,
+                    hex_value
+                );
+                record.w_value = Some(hex_value);
+                block_records.push(record.clone());
+            }
+        }
+        done += block_records.len();
+        completed(&block_records);
+    }
+
+    Ok(Retried {
+        value: readable,
+        retries,
+    })
+}
+
+// 将所有可写寄存器的设置值写入器件，返回写入的寄存器数量及合计的重试次数
+// 连续的可写地址合并为一次写多个寄存器 (0x10)，每帧最多123个，按 policy 重试；
+// 每块开始前检查 cancel，已停止时返回错误；
+// progress(当前块序号, 总块数, "块地址范围:首个寄存器名")，completed(本块写完的记录)
+pub async fn write_device_registers<F, C>(
+    port_manager: Arc<dyn ModbusTransport>,
+    records: &[RegisterRecord],
+    policy: &RetryPolicy,
+    cancel: &CancellationToken,
+    mut progress: F,
+    mut completed: C,
+) -> anyhow::Result<Retried<usize>>
+where
+    F: FnMut(usize, usize, &str) + Send,
+    C: FnMut(&[RegisterRecord]) + Send,
+{
+    // 过滤只有RW（可读写）的记录
+    let writable_records: Vec<&RegisterRecord> = records
+        .iter()
+        .filter(|record| is_writable(record))
+        .collect();
+
+    if writable_records.is_empty() {
+        return Err(anyhow::anyhow!("没有找到可写入的寄存器"));
+    }
+
+    // 解析写入值并按寄存器宽度拆分为 Modbus 寄存器的值，按地址排序
+    // 同一地址的多行按各自的可写位合并，可写位重叠且值不同时报错
+    let owners = word_owners(writable_records.iter().copied());
+    let mut values: BTreeMap<u16, (u16, u16)> = BTreeMap::new();
+    for record in &writable_records {
+        let def = &record.def;
+        let write_value = def
+            .parse_value(&record.value)
+            .map_err(|e| anyhow::anyhow!("寄存器 {} 的设置值无效: {}", record.register, e))?;
+        let words = def.split_words(write_value);
+        let masks = def.split_words(def.write_mask);
+        for ((address, word), mask) in def.addresses().into_iter().zip(words).zip(masks) {
+            let Some((merged, merged_mask)) = values.get_mut(&address) else {
+                values.insert(address, (word, mask));
+                continue;
+            };
+            if (*merged ^ word) & *merged_mask & mask != 0 {
+                return Err(anyhow::anyhow!(
+                    "寄存器 {} 与同一地址 0x{:04X} 的其他行写入值冲突",
+                    record.register,
+                    address
+                ));
+            }
+            *merged = (*merged & *merged_mask) | (word & mask);
+            *merged_mask |= mask;
+        }
+    }
+
+    let addresses: Vec<u16> = values.keys().copied().collect();
+    let blocks = group_register_blocks(&addresses, MAX_WRITE_REGISTERS);
+    let total_blocks = blocks.len();
+    let mut retries = 0;
+    let mut done = 0;
+
+    for (index, (start, count)) in blocks.into_iter().enumerate() {
+        if cancel.is_cancelled() {
+            return Err(cancelled_error(done, writable_records.len()));
+        }
+
+        let block_label = format!("0x{:04X}-0x{:04X}", start, start + (count - 1));
+        let first_name = writable_records[owners[&start][0]].register.as_str();
+        progress(
+            index,
+            total_blocks,
+            &format!("{}:{}", block_label, first_name),
+        );
+
+        let block_values: Vec<u16> = (start..=start + (count - 1))
+            .map(|address| values[&address].0)
+            .collect();
+
+        let result = with_retry(policy, || async {
+            if count == 1 {
+                port_manager
+                    .write_register(DEVICE_SLAVE_ADDRESS, start, block_values[0])
+                    .await
+            } else {
+                port_manager
+                    .write_registers(DEVICE_SLAVE_ADDRESS, start, &block_values)
+                    .await
+            }
+        })
+        .await;
+
+        match result {
+            Ok(written) => {
+                retries += written.retries;
+                log::info!("成功写入 {} = {:04X?}", block_label, block_values);
+                // 最后一个字也已写入的寄存器
+                let block_records: Vec<RegisterRecord> = (start..=start + (count - 1))
+                    .flat_map(|address| {
+                        owners[&address]
+                            .iter()
+                            .map(|&owner| writable_records[owner])
+                            .filter(move |record| is_last_word(record, address))
+                    })
+                    .cloned()
+                    .collect();
+                done += block_records.len();
+                completed(&block_records);
+            }
+            Err(e) => {
+                log::error!("写入寄存器块失败 {} - {:#}", block_label, e);
+                return Err(anyhow::anyhow!(
+                    "写入寄存器块失败 {} - {:#}",
+                    block_label,
+                    e
+                ));
+            }
+        }
+    }
+
+    Ok(Retried {
+        value: writable_records.len(),
+        retries,
+    })
+}
+
+// 配置器件前差异预览中的一行
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriteDiff {
+    pub record: RegisterRecord,
+    // 器件当前值，只写寄存器无法读取时为 None
+    pub current: Option<u32>,
+    // 将要写入的值
+    pub target: u32,
+}
+
+impl WriteDiff {
+    // 可写的位与写入值相同时跳过写入；无法读取当前值时总是写入
+    pub fn will_change(&self) -> bool {
+        self.current
+            .is_none_or(|current| (current ^ self.target) & self.record.def.write_mask != 0)
+    }
+}
+
+// 读取所有可写寄存器的当前值，与设置值比较，不向器件写入任何数据
+// 只写寄存器不读取；连续地址合并为块读取，按 policy 重试；
+// 每块开始前检查 cancel，已停止时返回错误；
+// progress(当前块序号, 总块数, 块地址范围)
+pub async fn preview_device_write<F>(
+    port_manager: Arc<dyn ModbusTransport>,
+    records: &[RegisterRecord],
+    policy: &RetryPolicy,
+    cancel: &CancellationToken,
+    mut progress: F,
+) -> anyhow::Result<Retried<Vec<WriteDiff>>>
+where
+    F: FnMut(usize, usize, &str) + Send,
+{
+    let mut writable: Vec<&RegisterRecord> = records
+        .iter()
+        .filter(|record| is_writable(record))
+        .collect();
+    if writable.is_empty() {
+        return Err(anyhow::anyhow!("没有找到可写入的寄存器"));
+    }
+    writable.sort_by_key(|record| record.def.address);
+
+    let mut diffs = Vec::new();
+    for record in writable {
+        let target = record
+            .def
+            .parse_value(&record.value)
+            .map_err(|e| anyhow::anyhow!("寄存器 {} 的设置值无效: {}", record.register, e))?;
+        diffs.push(WriteDiff {
+            record: record.clone(),
+            current: None,
+            target,
+        });
+    }
+
+    let readable: Vec<usize> = (0..diffs.len())
+        .filter(|&index| is_readable(&diffs[index].record))
+        .collect();
+    let owners = word_owners(readable.iter().map(|&index| &diffs[index].record));
+    let mut words = vec![Vec::new(); readable.len()];
+
+    let addresses: Vec<u16> = owners.keys().copied().collect();
+    let blocks = group_register_blocks(&addresses, MAX_READ_REGISTERS);
+    let total_blocks = blocks.len();
+    let mut retries = 0;
+    let mut done = 0;
+
+    for (index, (start, count)) in blocks.into_iter().enumerate() {
+        if cancel.is_cancelled() {
+            return Err(cancelled_error(done, readable.len()));
+        }
+
+        let block_label = format!("0x{:04X}-0x{:04X}", start, start + (count - 1));
+        progress(index, total_blocks, &block_label);
+
+        let read = with_retry(policy, || {
+            port_manager.read_registers(DEVICE_SLAVE_ADDRESS, start, count)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("读取寄存器块失败 {} - {:#}", block_label, e))?;
+        retries += read.retries;
+
+        for (address, value) in (start..).zip(read.value) {
+            for &owner in &owners[&address] {
+                words[owner].push(value);
+                let diff = &mut diffs[readable[owner]];
+                if is_last_word(&diff.record, address) {
+                    diff.current = Some(diff.record.def.join_words(&words[owner]));
+                    done += 1;
+                }
+            }
+        }
+    }
+
+    Ok(Retried {
+        value: diffs,
+        retries,
+    })
+}
+
+// 单个寄存器的回读校验结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerifyOutcome {
+    // 掩码内的位与写入值一致
+    Match,
+    // 掩码内的位与写入值不一致，actual 为读回的值
+    Mismatch { expected: u32, actual: u32 },
+    // 回读失败
+    Failed(String),
+    // 只写寄存器，无法回读校验
+    NotReadable,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifyResult {
+    pub record: RegisterRecord,
+    pub outcome: VerifyOutcome,
+}
+
+// 校验结果汇总
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VerifySummary {
+    pub verified: usize,
+    pub mismatched: usize,
+    pub failed: usize,
+    // 只写寄存器，未校验
+    pub unverifiable: usize,
+}
+
+impl VerifySummary {
+    pub fn from_results(results: &[VerifyResult]) -> Self {
+        let mut summary = Self::default();
+        for result in results {
+            match result.outcome {
+                VerifyOutcome::Match => summary.verified += 1,
+                VerifyOutcome::Mismatch { .. } => summary.mismatched += 1,
+                VerifyOutcome::Failed(_) => summary.failed += 1,
+                VerifyOutcome::NotReadable => summary.unverifiable += 1,
+            }
+        }
+        summary
+    }
+
+    pub fn all_verified(&self) -> bool {
+        self.mismatched == 0 && self.failed == 0
+    }
+}
+
+impl std::fmt::Display for VerifySummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "校验通过 {}，不一致 {}，失败 {}",
+            self.verified, self.mismatched, self.failed
+        )?;
+        if self.unverifiable > 0 {
+            write!(f, "，只写未校验 {}", self.unverifiable)?;
+        }
+        Ok(())
+    }
+}
+
+// 回读已写入的寄存器，按每个寄存器的掩码与写入值比较
+// 只写寄存器无法回读，记为未校验，也不参与块读取；
+// 连续地址合并为块读取，按 policy 重试；某块读取失败时该块的寄存器记为失败并继续；
+// 每块开始前检查 cancel，已停止时返回错误；
+// progress(当前块序号, 总块数, 块地址范围)，completed(本块的校验结果)
+pub async fn verify_device_registers<F, C>(
+    port_manager: Arc<dyn ModbusTransport>,
+    written: &[RegisterRecord],
+    policy: &RetryPolicy,
+    cancel: &CancellationToken,
+    mut progress: F,
+    mut completed: C,
+) -> anyhow::Result<Retried<Vec<VerifyResult>>>
+where
+    F: FnMut(usize, usize, &str) + Send,
+    C: FnMut(&[VerifyResult]) + Send,
+{
+    let (mut written, write_only): (Vec<&RegisterRecord>, Vec<&RegisterRecord>) =
+        written.iter().partition(|record| is_readable(record));
+    let mut results: Vec<VerifyResult> = write_only
+        .into_iter()
+        .map(|record| VerifyResult {
+            record: record.clone(),
+            outcome: VerifyOutcome::NotReadable,
+        })
+        .collect();
+    if !results.is_empty() {
+        completed(&results);
+    }
+    let total = results.len() + written.len();
+
+    // 先解析全部写入值和掩码，格式错误时不开始回读
+    written.sort_by_key(|record| record.def.address);
+    let mut expected = Vec::new();
+    for record in &written {
+        let value = record
+            .def
+            .parse_value(&record.value)
+            .map_err(|e| anyhow::anyhow!("寄存器 {} 的设置值无效: {}", record.register, e))?;
+        let mask = register_mask(record)
+            .map_err(|e| anyhow::anyhow!("寄存器 {} 的掩码无效: {}", record.register, e))?;
+        expected.push((value, mask));
+    }
+    let owners = word_owners(written.iter().copied());
+    // 各寄存器已读回的字，以及所在块的回读错误
+    let mut words = vec![Vec::new(); written.len()];
+    let mut errors: Vec<Option<String>> = vec![None; written.len()];
+
+    let addresses: Vec<u16> = owners.keys().copied().collect();
+    let blocks = group_register_blocks(&addresses, MAX_READ_REGISTERS);
+    let total_blocks = blocks.len();
+    let mut retries = 0;
+
+    for (index, (start, count)) in blocks.into_iter().enumerate() {
+        if cancel.is_cancelled() {
+            return Err(cancelled_error(results.len(), total));
+        }
+
+        let block_label = format!("0x{:04X}-0x{:04X}", start, start + (count - 1));
+        progress(index, total_blocks, &block_label);
+
+        let read = with_retry(policy, || {
+            port_manager.read_registers(DEVICE_SLAVE_ADDRESS, start, count)
+        })
+        .await;
+
+        let mut block_results = Vec::new();
+        for address in start..=start + (count - 1) {
+            for &owner in &owners[&address] {
+                match &read {
+                    Ok(read) => words[owner].push(read.value[(address - start) as usize]),
+                    Err(e) => {
+                        errors[owner].get_or_insert_with(|| format!("{:#}", e));
+                    }
+                }
+
+                let record = written[owner];
+                if !is_last_word(record, address) {
+                    continue;
+                }
+
+                let (value, mask) = expected[owner];
+                let outcome = match &errors[owner] {
+                    Some(error) => VerifyOutcome::Failed(error.clone()),
+                    None => {
+                        let actual = record.def.join_words(&words[owner]);
+                        if actual & mask == value & mask {
+                            VerifyOutcome::Match
+                        } else {
+                            log::warn!(
+                                "校验不一致: {}:{} 写入 {}，读回 {}，掩码 {}",
+                                record.page_addr,
+                                record.register,
+                                record.def.format_value(value),
+                                record.def.format_value(actual),
+                                record.def.format_value(mask)
+                            );
+                            VerifyOutcome::Mismatch {
+                                expected: value,
+                                actual,
+                            }
+                        }
+                    }
+                };
+                block_results.push(VerifyResult {
+                    record: record.clone(),
+                    outcome,
+                });
+            }
+        }
+
+        match read {
+            Ok(read) => retries += read.retries,
+            Err(e) => log::error!("回读寄存器块失败 {} - {:#}", block_label, e),
+        }
+        completed(&block_results);
+        results.extend(block_results);
+    }
+
+    Ok(Retried {
+        value: results,
+        retries,
+    })
+}
+
+// 读取一个芯片的 IO 电平 (寄存器最低位)，按 io_registers 的顺序返回
+// 连续的地址合并为一次读取
+pub async fn read_io_status(
+    port_manager: Arc<dyn ModbusTransport>,
+    io_registers: &[u16],
+) -> anyhow::Result<Vec<i32>> {
+    let mut levels = HashMap::new();
+    for (start, count) in group_register_blocks(io_registers, MAX_READ_REGISTERS) {
+        let values = port_manager
+            .read_registers(DEVICE_SLAVE_ADDRESS, start, count)
+            .await?;
+        for (offset, value) in values.into_iter().enumerate() {
+            levels.insert(start + offset as u16, (value & 1) as i32);
+        }
+    }
+    Ok(io_registers.iter().map(|address| levels[address]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chip_detection::{ChipType, detect_all_chips};
+    use crate::csv_handler::CsvHandler;
+    use crate::serial::mock::MockTransport;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    async fn open_mock(registers: impl IntoIterator<Item = (u16, u16)>) -> Arc<MockTransport> {
+        let mock =
+            Arc::new(MockTransport::new("mock", DEVICE_SLAVE_ADDRESS).with_registers(registers));
+        mock.open().await.unwrap();
+        mock
+    }
+
+    fn write_csv(lines: &[&str]) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "Page_Addr,Register,R_W,Value").unwrap();
+        for line in lines {
+            writeln!(file, "{}", line).unwrap();
+        }
+        file
+    }
+
+    #[test]
+    fn test_parse_page_addr_and_value() {
+        assert_eq!(parse_page_addr("0x4000").unwrap(), 0x4000);
+        assert_eq!(parse_page_addr("16").unwrap(), 16);
+        assert!(parse_page_addr("0xZZ").is_err());
+        assert_eq!(parse_register_value("0X1f").unwrap(), 0x1F);
+        assert!(parse_register_value("abc").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_detect_chips_with_mock() {
+        let mock = open_mock([(0x4000, 0x1C), (0xC000, 0x11)]).await;
+        let (chip1, chip2) = detect_all_chips(mock).await;
+        assert_eq!(
+            (chip1.chip_type, chip2.chip_type),
+            (ChipType::family("MALD"), ChipType::family("MATA"))
+        );
+        assert_eq!(chip2.chip_id, Some(0x11));
+        assert_eq!(chip2.page_base, 0xC000);
+
+        // 芯片二不存在时只识别芯片一
+        let mock = open_mock([(0x4000, 0x1D), (0x4001, 0x05)]).await;
+        let (chip1, chip2) = detect_all_chips(mock).await;
+        assert_eq!(
+            (chip1.chip_type.clone(), chip2.chip_type),
+            (ChipType::family("MALD"), ChipType::Unknown)
+        );
+        assert_eq!(chip1.to_string(), "MALD rev 5");
+        assert_eq!(chip1.revision, Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_read_csv_registers_from_mock() {
+        let csv = write_csv(&[
+            "0x4000,CHIPID,R,0x1C",
+            "0x4001,IO1,RW,0x00",
+            "0x4002,IO2,RW,0x00",
+            "0x4010,CTRL,W,0x05",
+        ]);
+        let records = CsvHandler::parse_csv_file(csv.path()).unwrap();
+        let mock = open_mock([(0x4000, 0x1C), (0x4001, 0x01), (0x4002, 0x1FF)]).await;
+
+        let mut progress = Vec::new();
+        let read = read_device_registers(
+            mock.clone(),
+            &records,
+            &RetryPolicy::default(),
+            &CancellationToken::new(),
+            |index, total, label| progress.push((index, total, label.to_string())),
+            |_| {},
+        )
+        .await
+        .unwrap();
+        assert_eq!(read.retries, 0);
+        let read = read.value;
+
+        // 只写寄存器不读取，连续地址合并为一次读取
+        assert_eq!(read.len(), 3);
+        assert_eq!(read[0].w_value.as_deref(), Some("0x1C"));
+        assert_eq!(read[1].w_value.as_deref(), Some("0x01"));
+        // 超出 8 位的读数完整显示，不截断
+        assert_eq!(read[2].w_value.as_deref(), Some("0x1FF"));
+        assert_eq!(progress, vec![(0, 1, "0x4000-0x4002".to_string())]);
+        assert_eq!(mock.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_write_csv_registers_to_mock() {
+        let csv = write_csv(&[
+            "0x4000,CHIPID,R,0x1C",
+            "0x4001,IO1,RW,0x01",
+            "0x4002,IO2,RW,0x00",
+            "0x4003,IO3,RW,0x01",
+            "0x4010,CTRL,W,5",
+        ]);
+        let records = CsvHandler::parse_csv_file(csv.path()).unwrap();
+        let mock = open_mock((0x4000..=0x4010).map(|address| (address, 0))).await;
+
+        let written = write_device_registers(
+            mock.clone(),
+            &records,
+            &RetryPolicy::default(),
+            &CancellationToken::new(),
+            |_, _, _| {},
+            |_| {},
+        )
+        .await
+        .unwrap();
+        assert_eq!(written.value, 4);
+
+        // 只读寄存器不写入
+        assert_eq!(mock.register(0x4000), Some(0));
+        assert_eq!(mock.register(0x4001), Some(1));
+        assert_eq!(mock.register(0x4003), Some(1));
+        assert_eq!(mock.register(0x4010), Some(5));
+
+        // 0x4001-0x4003 用 0x10 一次写入，0x4010 单独用 0x06 写入
+        let function_codes: Vec<u8> = mock
+            .requests()
+            .iter()
+            .map(|request| request.get_function_code())
+            .collect();
+        assert_eq!(function_codes, vec![0x10, 0x06]);
+    }
+
+    #[tokio::test]
+    async fn test_write_failure_reports_block() {
+        let csv = write_csv(&["0x4001,IO1,RW,0x01", "0x4002,IO2,RW,0x00"]);
+        let records = CsvHandler::parse_csv_file(csv.path()).unwrap();
+        // 0x4002 不存在，从站返回非法数据地址异常
+        let mock = open_mock([(0x4001, 0)]).await;
+
+        let error = write_device_registers(
+            mock.clone(),
+            &records,
+            &RetryPolicy::default(),
+            &CancellationToken::new(),
+            |_, _, _| {},
+            |_| {},
+        )
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("0x4001-0x4002"));
+        // 异常响应不重试
+        assert_eq!(mock.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_read_retries_after_timeouts() {
+        let csv = write_csv(&["0x4000,CHIPID,R,0x1C", "0x4010,CTRL,RW,0x05"]);
+        let records = CsvHandler::parse_csv_file(csv.path()).unwrap();
+        let mock = open_mock([(0x4000, 0x1C), (0x4010, 0x05)]).await;
+        let policy = RetryPolicy {
+            initial_backoff: std::time::Duration::from_millis(1),
+            ..RetryPolicy::default()
+        };
+
+        // 两次超时后成功，结果报告重试次数
+        mock.drop_requests(2);
+        let cancel = CancellationToken::new();
+        let read = read_device_registers(
+            mock.clone(),
+            &records,
+            &policy,
+            &cancel,
+            |_, _, _| {},
+            |_| {},
+        )
+        .await
+        .unwrap();
+        assert_eq!(read.retries, 2);
+        assert_eq!(read.value[1].w_value.as_deref(), Some("0x05"));
+
+        // 超过重试次数时报告失败的块
+        mock.drop_requests(3);
+        let error = read_device_registers(mock, &records, &policy, &cancel, |_, _, _| {}, |_| {})
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("0x4000-0x4000"));
+        assert!(error.to_string().contains("已重试 2 次"));
+    }
+
+    #[tokio::test]
+    async fn test_write_cancelled_between_blocks() {
+        let csv = write_csv(&[
+            "0x4001,IO1,RW,0x01",
+            "0x4002,IO2,RW,0x01",
+            "0x4010,CTRL,RW,0x05",
+            "0x4020,GAIN,RW,0x07",
+        ]);
+        let records = CsvHandler::parse_csv_file(csv.path()).unwrap();
+        let mock = open_mock([(0x4001, 0), (0x4002, 0), (0x4010, 0), (0x4020, 0)]).await;
+
+        // 第一块写完后停止
+        let cancel = CancellationToken::new();
+        let mut finished = Vec::new();
+        let error = write_device_registers(
+            mock.clone(),
+            &records,
+            &RetryPolicy::default(),
+            &cancel,
+            |_, _, _| {},
+            |block| {
+                finished.extend(block.iter().map(|record| record.register.clone()));
+                cancel.cancel();
+            },
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(error.to_string(), "已停止，完成 2/4 个寄存器");
+        assert_eq!(finished, vec!["IO1", "IO2"]);
+        assert_eq!(mock.register(0x4002), Some(1));
+        assert_eq!(mock.register(0x4010), Some(0));
+        assert_eq!(mock.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_preview_write_diff() {
+        let csv = write_csv(&[
+            "0x4000,CHIPID,R,0x1C",
+            "0x4001,IO1,RW,0x01",
+            "0x4002,IO2,RW,0x00",
+            "0x4010,CTRL,W,0x05",
+        ]);
+        let records = CsvHandler::parse_csv_file(csv.path()).unwrap();
+        let mock = open_mock([(0x4000, 0x1C), (0x4001, 0x00), (0x4002, 0x00), (0x4010, 0)]).await;
+
+        let preview = preview_device_write(
+            mock.clone(),
+            &records,
+            &RetryPolicy::default(),
+            &CancellationToken::new(),
+            |_, _, _| {},
+        )
+        .await
+        .unwrap();
+
+        let diffs: Vec<(&str, Option<u32>, u32, bool)> = preview
+            .value
+            .iter()
+            .map(|diff| {
+                (
+                    diff.record.register.as_str(),
+                    diff.current,
+                    diff.target,
+                    diff.will_change(),
+                )
+            })
+            .collect();
+        // 只写寄存器无法读取当前值，总是写入
+        assert_eq!(
+            diffs,
+            vec![
+                ("IO1", Some(0x00), 0x01, true),
+                ("IO2", Some(0x00), 0x00, false),
+                ("CTRL", None, 0x05, true),
+            ]
+        );
+
+        // 预览只读取，不写入
+        assert_eq!(mock.register(0x4001), Some(0));
+        let function_codes: Vec<u8> = mock
+            .requests()
+            .iter()
+            .map(|request| request.get_function_code())
+            .collect();
+        assert_eq!(function_codes, vec![0x03]);
+    }
+
+    #[tokio::test]
+    async fn test_verify_with_masks() {
+        let csv = NamedTempFile::new().unwrap();
+        std::fs::write(
+            csv.path(),
+            "Page_Addr,Register,R_W,Value,Mask\n\
+             0x4001,IO1,RW,0x01,\n\
+             0x4002,IO2,RW,0x81,0x0F\n\
+             0x4003,IO3,RW,0x01,0x0F\n\
+             0x4010,CTRL,RW,0x05,\n",
+        )
+        .unwrap();
+        let records = CsvHandler::parse_csv_file(csv.path()).unwrap();
+        // 0x4002 的高位不在掩码内，0x4003 掩码内不一致，0x4010 不存在
+        let mock = open_mock([(0x4001, 0x01), (0x4002, 0x01), (0x4003, 0x03)]).await;
+
+        let verified = verify_device_registers(
+            mock.clone(),
+            &records,
+            &RetryPolicy::default(),
+            &CancellationToken::new(),
+            |_, _, _| {},
+            |_| {},
+        )
+        .await
+        .unwrap();
+
+        let outcomes: Vec<&VerifyOutcome> = verified
+            .value
+            .iter()
+            .map(|result| &result.outcome)
+            .collect();
+        assert_eq!(outcomes[0], &VerifyOutcome::Match);
+        assert_eq!(outcomes[1], &VerifyOutcome::Match);
+        assert_eq!(
+            outcomes[2],
+            &VerifyOutcome::Mismatch {
+                expected: 0x01,
+                actual: 0x03
+            }
+        );
+        assert!(matches!(outcomes[3], VerifyOutcome::Failed(_)));
+
+        let summary = VerifySummary::from_results(&verified.value);
+        assert_eq!(summary.to_string(), "校验通过 2，不一致 1，失败 1");
+        assert!(!summary.all_verified());
+        // 0x4001-0x4003 一次回读
+        assert_eq!(mock.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_verify_skips_write_only_registers() {
+        let csv = write_csv(&[
+            "0x4001,IO1,RW,0x01",
+            "0x4002,CMD,W,0x5A",
+            "0x4003,IO3,RW,0x00",
+        ]);
+        let records = CsvHandler::parse_csv_file(csv.path()).unwrap();
+        // 只写寄存器读取时返回非法地址异常，不应影响同一段地址的其他寄存器
+        let mock = open_mock([(0x4001, 0x01), (0x4003, 0x00)]).await;
+
+        let mut completed = Vec::new();
+        let verified = verify_device_registers(
+            mock.clone(),
+            &records,
+            &RetryPolicy::default(),
+            &CancellationToken::new(),
+            |_, _, _| {},
+            |results| completed.extend(results.iter().map(|result| result.record.register.clone())),
+        )
+        .await
+        .unwrap();
+
+        let outcomes: Vec<(&str, &VerifyOutcome)> = verified
+            .value
+            .iter()
+            .map(|result| (result.record.register.as_str(), &result.outcome))
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("CMD", &VerifyOutcome::NotReadable),
+                ("IO1", &VerifyOutcome::Match),
+                ("IO3", &VerifyOutcome::Match),
+            ]
+        );
+        assert_eq!(completed, vec!["CMD", "IO1", "IO3"]);
+
+        let summary = VerifySummary::from_results(&verified.value);
+        assert_eq!(
+            summary.to_string(),
+            "校验通过 2，不一致 0，失败 0，只写未校验 1"
+        );
+        assert!(summary.all_verified());
+        // 0x4001 和 0x4003 分别读取，不读只写的 0x4002
+        let reads: Vec<u16> = mock
+            .requests()
+            .iter()
+            .filter(|request| request.get_function_code() == 0x03)
+            .map(|request| u16::from_be_bytes([request.get_data()[0], request.get_data()[1]]))
+            .collect();
+        assert_eq!(reads, vec![0x4001, 0x4003]);
+    }
+
+    #[tokio::test]
+    async fn test_32_bit_register_words() {
+        let csv = NamedTempFile::new().unwrap();
+        std::fs::write(
+            csv.path(),
+            "Page_Addr,Register,R_W,Value,Width,Write_Mask\n\
+             0x4010,COUNTER,RW,0x12345678,32,\n\
+             0x4012,CTRL,RW,0x0105,16,0x00FF\n",
+        )
+        .unwrap();
+        let records = CsvHandler::parse_csv_file(csv.path()).unwrap();
+        let mock = open_mock([(0x4010, 0), (0x4011, 0), (0x4012, 0x0005)]).await;
+        let policy = RetryPolicy::default();
+        let cancel = CancellationToken::new();
+
+        // CTRL 只有低 8 位可写，高位不同不算修改
+        let preview = preview_device_write(mock.clone(), &records, &policy, &cancel, |_, _, _| {})
+            .await
+            .unwrap();
+        let changes: Vec<bool> = preview.value.iter().map(WriteDiff::will_change).collect();
+        assert_eq!(changes, vec![true, false]);
+
+        // 32 位寄存器高字在前，三个地址一次写入
+        let mut finished = Vec::new();
+        write_device_registers(
+            mock.clone(),
+            &records,
+            &policy,
+            &cancel,
+            |_, _, _| {},
+            |block| finished.extend(block.iter().map(|record| record.register.clone())),
+        )
+        .await
+        .unwrap();
+        assert_eq!(finished, vec!["COUNTER", "CTRL"]);
+        assert_eq!(mock.register(0x4010), Some(0x1234));
+        assert_eq!(mock.register(0x4011), Some(0x5678));
+
+        let read = read_device_registers(
+            mock.clone(),
+            &records,
+            &policy,
+            &cancel,
+            |_, _, _| {},
+            |_| {},
+        )
+        .await
+        .unwrap();
+        assert_eq!(read.value[0].w_value.as_deref(), Some("0x12345678"));
+        assert_eq!(read.value[1].w_value.as_deref(), Some("0x0105"));
+
+        // 没有 Mask 列时按可写的位校验
+        mock.set_register(0x4012, 0xFF05);
+        let verified =
+            verify_device_registers(mock, &records, &policy, &cancel, |_, _, _| {}, |_| {})
+                .await
+                .unwrap();
+        assert!(VerifySummary::from_results(&verified.value).all_verified());
+    }
+
+    #[tokio::test]
+    async fn test_rows_sharing_address() {
+        let csv = NamedTempFile::new().unwrap();
+        std::fs::write(
+            csv.path(),
+            "Page_Addr,Register,R_W,Value,Width,Write_Mask\n\
+             0x4030,MODE,RW,0x03,,0x0F\n\
+             0x4030,GAIN,RW,0x50,,0xF0\n\
+             0x4031,CTRL,RW,0x01,,\n",
+        )
+        .unwrap();
+        let records = CsvHandler::parse_csv_file(csv.path()).unwrap();
+        let mock = open_mock([(0x4030, 0), (0x4031, 0)]).await;
+        let policy = RetryPolicy::default();
+        let cancel = CancellationToken::new();
+
+        // 同一地址的两行按各自的可写位合并为一个值写入
+        let written = write_device_registers(
+            mock.clone(),
+            &records,
+            &policy,
+            &cancel,
+            |_, _, _| {},
+            |_| {},
+        )
+        .await
+        .unwrap();
+        assert_eq!(written.value, 3);
+        assert_eq!(mock.register(0x4030), Some(0x53));
+        assert_eq!(mock.requests().len(), 1);
+
+        // 两行都读到同一地址的值
+        let read = read_device_registers(
+            mock.clone(),
+            &records,
+            &policy,
+            &cancel,
+            |_, _, _| {},
+            |_| {},
+        )
+        .await
+        .unwrap();
+        let values: Vec<&str> = read
+            .value
+            .iter()
+            .map(|record| record.w_value.as_deref().unwrap())
+            .collect();
+        assert_eq!(values, vec!["0x53", "0x53", "0x01"]);
+
+        let verified = verify_device_registers(
+            mock.clone(),
+            &records,
+            &policy,
+            &cancel,
+            |_, _, _| {},
+            |_| {},
+        )
+        .await
+        .unwrap();
+        assert!(VerifySummary::from_results(&verified.value).all_verified());
+
+        // 可写位重叠且值不同时不写入
+        let mut conflicting = records.clone();
+        conflicting[1].def.write_mask = 0xFF;
+        let error =
+            write_device_registers(mock, &conflicting, &policy, &cancel, |_, _, _| {}, |_| {})
+                .await
+                .unwrap_err();
+        assert!(error.to_string().contains("0x4030"));
+    }
+
+    #[tokio::test]
+    async fn test_read_io_status_from_mock() {
+        let mock = open_mock([
+            (0x4001, 1),
+            (0x4002, 0),
+            (0x4003, 3),
+            (0xC001, 0),
+            (0xC002, 1),
+            (0xC003, 0),
+        ])
+        .await;
+
+        assert_eq!(
+            read_io_status(mock.clone(), &io_registers(CHIP1_IO_BASE))
+                .await
+                .unwrap(),
+            vec![1, 0, 1]
+        );
+        assert_eq!(
+            read_io_status(mock.clone(), &io_registers(CHIP2_IO_BASE))
+                .await
+                .unwrap(),
+            vec![0, 1, 0]
+        );
+
+        // IO 变化后下一次轮询可见
+        mock.set_register(0x4002, 1);
+        assert_eq!(
+            read_io_status(mock.clone(), &io_registers(CHIP1_IO_BASE))
+                .await
+                .unwrap(),
+            vec![1, 1, 1]
+        );
+        // 连续的 IO 寄存器一次读取
+        assert_eq!(mock.requests().len(), 3);
+    }
+}
@@ -0,0 +1,344 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::csv_handler::RegisterRecord;
+
+// 快照中一个寄存器的记录: 寄存器表的原始各列加上读回的值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotRegister {
+    pub page_addr: String,
+    pub register: String,
+    pub r_w: String,
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mask: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reset: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_mask: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fields: Option<String>,
+    // 未读取过的寄存器为 None
+    pub read_value: Option<String>,
+}
+
+impl SnapshotRegister {
+    // 按寄存器表的各列还原寄存器记录，读回的值放在 w_value
+    pub fn to_record(&self) -> anyhow::Result<RegisterRecord> {
+        let mut record = RegisterRecord::new(
+            self.page_addr.clone(),
+            self.register.clone(),
+            self.r_w.clone(),
+            self.value.clone(),
+        );
+        record.mask = self.mask.clone();
+        record.width = self.width.clone();
+        record.reset = self.reset.clone();
+        record.write_mask = self.write_mask.clone();
+        record.fields = self.fields.clone();
+        record.def = record
+            .parse_def()
+            .map_err(|e| anyhow!("寄存器 {} 定义无效: {}", self.register, e))?;
+        record.w_value = self.read_value.clone();
+        Ok(record)
+    }
+}
+
+impl From<&RegisterRecord> for SnapshotRegister {
+    fn from(record: &RegisterRecord) -> Self {
+        Self {
+            page_addr: record.page_addr.clone(),
+            register: record.register.clone(),
+            r_w: record.r_w.clone(),
+            value: record.value.clone(),
+            mask: record.mask.clone(),
+            width: record.width.clone(),
+            reset: record.reset.clone(),
+            write_mask: record.write_mask.clone(),
+            fields: record.fields.clone(),
+            read_value: record.w_value.clone(),
+        }
+    }
+}
+
+// 某一时刻从器件读回的寄存器表，可导出为 CSV 或 JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    // 本地时间，例如 "2024-05-01 14:03:27"
+    pub timestamp: String,
+    pub port: String,
+    pub chip: String,
+    pub registers: Vec<SnapshotRegister>,
+}
+
+// 导出的 CSV 中的一行
+#[derive(Deserialize)]
+struct SnapshotCsvRow {
+    #[serde(rename = "Page_Addr")]
+    page_addr: String,
+    #[serde(rename = "Register")]
+    register: String,
+    #[serde(rename = "R_W")]
+    r_w: String,
+    #[serde(rename = "Value")]
+    value: String,
+    #[serde(rename = "Mask", default)]
+    mask: Option<String>,
+    #[serde(rename = "Width", default)]
+    width: Option<String>,
+    #[serde(rename = "Reset", default)]
+    reset: Option<String>,
+    #[serde(rename = "Write_Mask", default)]
+    write_mask: Option<String>,
+    #[serde(rename = "Fields", default)]
+    fields: Option<String>,
+    #[serde(rename = "Read_Value", default)]
+    read_value: Option<String>,
+    #[serde(rename = "Timestamp", default)]
+    timestamp: String,
+    #[serde(rename = "Port", default)]
+    port: String,
+    #[serde(rename = "Chip", default)]
+    chip: String,
+}
+
+// 寄存器表中可选的列，导出时只保留原文件中有的列
+type ColumnValue = fn(&SnapshotRegister) -> &Option<String>;
+
+const OPTIONAL_COLUMNS: [(&str, ColumnValue); 5] = [
+    ("Mask", |register| &register.mask),
+    ("Width", |register| &register.width),
+    ("Reset", |register| &register.reset),
+    ("Write_Mask", |register| &register.write_mask),
+    ("Fields", |register| &register.fields),
+];
+
+impl Snapshot {
+    // 以当前时间记录寄存器表
+    pub fn capture(records: &[RegisterRecord], port: &str, chip: &str) -> Self {
+        Self {
+            timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            port: port.to_string(),
+            chip: chip.to_string(),
+            registers: records.iter().map(SnapshotRegister::from).collect(),
+        }
+    }
+
+    // 默认文件名，例如 "snapshot_20240501_140327.csv"
+    pub fn file_name(&self, extension: &str) -> String {
+        let time: String = self
+            .timestamp
+            .chars()
+            .filter(char::is_ascii_digit)
+            .collect();
+        let (date, time) = time.split_at(time.len().min(8));
+        format!("snapshot_{}_{}.{}", date, time, extension)
+    }
+
+    // 原始各列加上 Read_Value、Timestamp、Port、Chip
+    pub fn to_csv(&self) -> anyhow::Result<String> {
+        let columns: Vec<_> = OPTIONAL_COLUMNS
+            .iter()
+            .filter(|(_, column)| {
+                self.registers
+                    .iter()
+                    .any(|register| column(register).is_some())
+            })
+            .collect();
+
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        let mut header = vec!["Page_Addr", "Register", "R_W", "Value"];
+        header.extend(columns.iter().map(|(name, _)| *name));
+        header.extend(["Read_Value", "Timestamp", "Port", "Chip"]);
+        writer.write_record(&header)?;
+
+        for register in &self.registers {
+            let mut row = vec![
+                register.page_addr.as_str(),
+                register.register.as_str(),
+                register.r_w.as_str(),
+                register.value.as_str(),
+            ];
+            row.extend(
+                columns
+                    .iter()
+                    .map(|(_, column)| column(register).as_deref().unwrap_or("")),
+            );
+            row.extend([
+                register.read_value.as_deref().unwrap_or(""),
+                self.timestamp.as_str(),
+                self.port.as_str(),
+                self.chip.as_str(),
+            ]);
+            writer.write_record(&row)?;
+        }
+
+        let bytes = writer.into_inner().map_err(|e| anyhow!("{}", e))?;
+        Ok(String::from_utf8(bytes)?)
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    // 按扩展名保存为 JSON 或 CSV
+    pub fn save(&self, file_path: &Path) -> anyhow::Result<()> {
+        save_csv_or_json(file_path, || self.to_csv(), || self.to_json())
+    }
+
+    // 读取导出的快照，按扩展名解析 JSON 或 CSV；
+    // 没有 Read_Value 等列的寄存器表也可以读取，此时各寄存器没有读回的值
+    pub fn load(file_path: &Path) -> anyhow::Result<Self> {
+        if is_json(file_path) {
+            let json = std::fs::read_to_string(file_path)?;
+            return Ok(serde_json::from_str(&json)?);
+        }
+
+        let mut reader = csv::Reader::from_path(file_path)?;
+        let mut snapshot = Self {
+            timestamp: String::new(),
+            port: String::new(),
+            chip: String::new(),
+            registers: Vec::new(),
+        };
+        for result in reader.deserialize() {
+            let row: SnapshotCsvRow = result?;
+            if snapshot.registers.is_empty() {
+                snapshot.timestamp = row.timestamp;
+                snapshot.port = row.port;
+                snapshot.chip = row.chip;
+            }
+            snapshot.registers.push(SnapshotRegister {
+                page_addr: row.page_addr,
+                register: row.register,
+                r_w: row.r_w,
+                value: row.value,
+                mask: row.mask,
+                width: row.width,
+                reset: row.reset,
+                write_mask: row.write_mask,
+                fields: row.fields,
+                read_value: row.read_value.filter(|value| !value.is_empty()),
+            });
+        }
+        Ok(snapshot)
+    }
+}
+
+fn is_json(file_path: &Path) -> bool {
+    file_path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
+
+// 扩展名为 .json 时保存 JSON，否则保存 CSV
+pub fn save_csv_or_json(
+    file_path: &Path,
+    to_csv: impl FnOnce() -> anyhow::Result<String>,
+    to_json: impl FnOnce() -> anyhow::Result<String>,
+) -> anyhow::Result<()> {
+    let content = if is_json(file_path) {
+        to_json()?
+    } else {
+        to_csv()?
+    };
+
+    let file_name = file_path
+        .to_str()
+        .ok_or_else(|| anyhow!("无效的文件路径: {:?}", file_path))?;
+    config::write_vec_to_file(file_name, &[content])
+}
+
+// 打开保存文件对话框，默认文件名带时间戳
+pub fn select_export_file(title: &str, default_name: &str) -> Option<std::path::PathBuf> {
+    FileDialog::new()
+        .add_filter("CSV Files", &["csv"])
+        .add_filter("JSON Files", &["json"])
+        .set_title(title)
+        .set_file_name(default_name)
+        .save_file()
+}
+
+// 选择要比较的快照文件，可以选择一个或两个
+pub fn select_snapshot_files() -> Option<Vec<std::path::PathBuf>> {
+    FileDialog::new()
+        .add_filter("Snapshot Files", &["csv", "json"])
+        .set_title("选择一个快照与设置值比较，或两个快照互相比较")
+        .pick_files()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv_handler::CsvHandler;
+    use tempfile::NamedTempFile;
+
+    fn snapshot() -> Snapshot {
+        let csv = NamedTempFile::new().unwrap();
+        std::fs::write(
+            csv.path(),
+            "Page_Addr,Register,R_W,Value,Mask\n\
+             0x4000,CHIPID,R,0x1C,\n\
+             0x4001,IO1,RW,0x01,0x01\n",
+        )
+        .unwrap();
+        let mut records = CsvHandler::parse_csv_file(csv.path()).unwrap();
+        records[0].w_value = Some("0x1C".to_string());
+
+        let mut snapshot = Snapshot::capture(&records, "COM7", "MALD rev 1");
+        snapshot.timestamp = "2024-05-01 14:03:27".to_string();
+        snapshot
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let snapshot = snapshot();
+        assert_eq!(snapshot.file_name("csv"), "snapshot_20240501_140327.csv");
+
+        // 原文件中没有的可选列不导出
+        let csv = snapshot.to_csv().unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Page_Addr,Register,R_W,Value,Mask,Read_Value,Timestamp,Port,Chip",
+                "0x4000,CHIPID,R,0x1C,,0x1C,2024-05-01 14:03:27,COM7,MALD rev 1",
+                "0x4001,IO1,RW,0x01,0x01,,2024-05-01 14:03:27,COM7,MALD rev 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_export_json() {
+        let snapshot = snapshot();
+        let file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        snapshot.save(file.path()).unwrap();
+
+        let json = std::fs::read_to_string(file.path()).unwrap();
+        assert_eq!(Snapshot::load(file.path()).unwrap(), snapshot);
+        assert!(!json.contains("write_mask"));
+    }
+
+    #[test]
+    fn test_load_exported_csv() {
+        let snapshot = snapshot();
+        let file = tempfile::Builder::new().suffix(".csv").tempfile().unwrap();
+        snapshot.save(file.path()).unwrap();
+
+        let loaded = Snapshot::load(file.path()).unwrap();
+        assert_eq!(loaded.timestamp, snapshot.timestamp);
+        assert_eq!(loaded.chip, "MALD rev 1");
+        assert_eq!(loaded.registers[0].read_value.as_deref(), Some("0x1C"));
+        assert_eq!(loaded.registers[1].read_value, None);
+
+        let record = loaded.registers[1].to_record().unwrap();
+        assert_eq!(record.def.address, 0x4001);
+        assert_eq!(record.mask.as_deref(), Some("0x01"));
+    }
+}
@@ -0,0 +1,123 @@
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use tokio_util::sync::CancellationToken;
+
+// 可以中途停止的器件批量操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobKind {
+    ReadDevice,
+    WriteDevice,
+    // 配置器件前读取当前值
+    PreviewWrite,
+}
+
+impl std::fmt::Display for JobKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ReadDevice => write!(f, "器件读取"),
+            Self::WriteDevice => write!(f, "器件写入"),
+            Self::PreviewWrite => write!(f, "写入预览"),
+        }
+    }
+}
+
+struct ActiveJob {
+    id: u64,
+    kind: JobKind,
+    cancel: CancellationToken,
+}
+
+#[derive(Default)]
+struct JobSlot {
+    active: Option<ActiveJob>,
+    next_id: u64,
+}
+
+lazy_static! {
+    // 同一时间只运行一个器件读写任务，避免两个任务同时修改寄存器表
+    static ref JOB_SLOT: Mutex<JobSlot> = Mutex::new(JobSlot::default());
+}
+
+// 正在运行的任务，释放时从任务槽中移除
+pub struct Job {
+    id: u64,
+    kind: JobKind,
+    cancel: CancellationToken,
+}
+
+impl Job {
+    // 开始一个任务，已有任务在运行时返回错误
+    pub fn start(kind: JobKind) -> anyhow::Result<Self> {
+        let mut slot = JOB_SLOT.lock().unwrap();
+        if let Some(active) = &slot.active {
+            return Err(anyhow::anyhow!("{}正在进行，请等待完成或停止", active.kind));
+        }
+
+        slot.next_id += 1;
+        let job = Self {
+            id: slot.next_id,
+            kind,
+            cancel: CancellationToken::new(),
+        };
+        slot.active = Some(ActiveJob {
+            id: job.id,
+            kind,
+            cancel: job.cancel.clone(),
+        });
+        log::info!("{}开始", kind);
+        Ok(job)
+    }
+
+    pub fn cancel_token(&self) -> &CancellationToken {
+        &self.cancel
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        let mut slot = JOB_SLOT.lock().unwrap();
+        if slot
+            .active
+            .as_ref()
+            .is_some_and(|active| active.id == self.id)
+        {
+            slot.active = None;
+        }
+        log::info!("{}结束", self.kind);
+    }
+}
+
+// 请求停止正在运行的任务，返回被停止的任务类型
+pub fn cancel_active_job() -> Option<JobKind> {
+    let slot = JOB_SLOT.lock().unwrap();
+    let active = slot.active.as_ref()?;
+    active.cancel.cancel();
+    log::info!("请求停止{}", active.kind);
+    Some(active.kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_job_and_cancel() {
+        let job = Job::start(JobKind::WriteDevice).unwrap();
+        // 运行中不能再开始其他任务
+        assert!(Job::start(JobKind::ReadDevice).is_err());
+
+        assert_eq!(cancel_active_job(), Some(JobKind::WriteDevice));
+        assert!(job.is_cancelled());
+        assert!(job.cancel_token().is_cancelled());
+
+        drop(job);
+        assert_eq!(cancel_active_job(), None);
+        let job = Job::start(JobKind::ReadDevice).unwrap();
+        assert!(!job.is_cancelled());
+    }
+}
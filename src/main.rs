@@ -4,11 +4,18 @@
 
 use std::error::Error;
 
+mod chip_db;
 mod chip_detection;
 mod config;
 mod csv_handler;
+mod device_io;
+mod export;
+mod job;
+mod register_map;
+mod register_store;
 mod serial;
 mod serial_impl;
+mod snapshot_compare;
 mod ui_handlers;
 
 slint::include_modules!();
@@ -16,6 +23,7 @@ slint::include_modules!();
 fn main() -> Result<(), Box<dyn Error>> {
     config::init_config();
     log::info!("init_config");
+    chip_db::init_chip_database(std::path::Path::new(chip_db::CHIP_DATABASE_FILE));
 
     let ui = AppWindow::new()?;
 
@@ -25,6 +33,22 @@ fn main() -> Result<(), Box<dyn Error>> {
     ui.global::<AppState>().set_connect_status("未连接".into());
     ui.global::<AppState>().set_show_chip_info(false);
 
+    // --simulator-pty [寄存器表.csv]: 在伪终端上启动设备模拟器，并将其路径填入端口
+    #[cfg(unix)]
+    if let Some(index) = std::env::args().position(|arg| arg == "--simulator-pty") {
+        let url = match std::env::args().nth(index + 1) {
+            Some(csv_path) if !csv_path.starts_with("--") => {
+                format!("{}{}", serial::simulator::SIMULATOR_SCHEME, csv_path)
+            }
+            _ => serial::simulator::SIMULATOR_SCHEME.to_string(),
+        };
+        let simulator = std::sync::Arc::new(serial::simulator::DeviceSimulator::from_url(&url)?);
+        let runtime = config::get_runtime();
+        let _guard = runtime.enter();
+        let path = simulator.spawn_pty(tokio_util::sync::CancellationToken::new())?;
+        ui.global::<AppState>().set_port_value(path.into());
+    }
+
     // 设置UI事件处理器
     ui_handlers::setup_ui_handlers(&ui);
 
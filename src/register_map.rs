@@ -0,0 +1,334 @@
+use anyhow::anyhow;
+
+// 寄存器宽度，32 位寄存器占用两个连续的 Modbus 保持寄存器 (高字在前)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RegisterWidth {
+    // CSV 中没有 Width 列时按 8 位寄存器处理，与原有寄存器表一致
+    #[default]
+    W8,
+    W16,
+    W32,
+}
+
+impl RegisterWidth {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        match text.trim() {
+            "" | "8" => Ok(Self::W8),
+            "16" => Ok(Self::W16),
+            "32" => Ok(Self::W32),
+            other => Err(anyhow!("无效的寄存器宽度: {}，应为 8/16/32", other)),
+        }
+    }
+
+    pub fn bits(self) -> u32 {
+        match self {
+            Self::W8 => 8,
+            Self::W16 => 16,
+            Self::W32 => 32,
+        }
+    }
+
+    // 占用的 Modbus 寄存器数
+    pub fn word_count(self) -> u16 {
+        match self {
+            Self::W32 => 2,
+            _ => 1,
+        }
+    }
+
+    // 全部位为 1 的掩码
+    pub fn full_mask(self) -> u32 {
+        u32::MAX >> (32 - self.bits())
+    }
+
+    // 按宽度补齐位数的十六进制，超出宽度的值完整显示而不截断
+    pub fn format(self, value: u32) -> String {
+        format!("0x{:0width$X}", value, width = (self.bits() / 4) as usize)
+    }
+}
+
+// 寄存器访问权限
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Access {
+    #[default]
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+}
+
+impl Access {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        match text.trim().to_uppercase().as_str() {
+            "R" | "RO" => Ok(Self::ReadOnly),
+            "W" | "WO" => Ok(Self::WriteOnly),
+            "RW" | "WR" | "R/W" => Ok(Self::ReadWrite),
+            other => Err(anyhow!("无效的读写属性: {}", other)),
+        }
+    }
+
+    pub fn is_readable(self) -> bool {
+        self != Self::WriteOnly
+    }
+
+    pub fn is_writable(self) -> bool {
+        self != Self::ReadOnly
+    }
+}
+
+// 解析十六进制 (0x 前缀) 或十进制数值
+pub fn parse_number(text: &str) -> anyhow::Result<u32> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).map_err(|_| anyhow!("无效的值格式: {}", text)),
+        None => text
+            .parse::<u32>()
+            .map_err(|_| anyhow!("无效的值: {}", text)),
+    }
+}
+
+// 寄存器中的位域，可以为取值命名，例如 MODE[2:1]=SLOW:0|FAST:1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitField {
+    pub name: String,
+    // 最低位
+    pub lsb: u8,
+    // 位数
+    pub width: u8,
+    // 命名的取值
+    pub values: Vec<(String, u32)>,
+}
+
+impl BitField {
+    // 解析一个位域定义: NAME[msb:lsb] 或 NAME[bit]，可带 =名称:值|名称:值
+    fn parse(text: &str) -> anyhow::Result<Self> {
+        let (field, values) = match text.split_once('=') {
+            Some((field, values)) => (field, Some(values)),
+            None => (text, None),
+        };
+
+        let invalid = || anyhow!("无效的位域定义: {}", text);
+        let (name, bits) = field.trim().split_once('[').ok_or_else(invalid)?;
+        let bits = bits.strip_suffix(']').ok_or_else(invalid)?;
+        let (msb, lsb) = match bits.split_once(':') {
+            Some((msb, lsb)) => (msb.trim(), lsb.trim()),
+            None => (bits.trim(), bits.trim()),
+        };
+        let msb: u8 = msb.parse().map_err(|_| invalid())?;
+        let lsb: u8 = lsb.parse().map_err(|_| invalid())?;
+        if name.trim().is_empty() || msb < lsb || msb >= 32 {
+            return Err(invalid());
+        }
+
+        let values = match values {
+            Some(values) => values
+                .split('|')
+                .map(|entry| {
+                    let (label, value) = entry.split_once(':').ok_or_else(invalid)?;
+                    Ok((label.trim().to_string(), parse_number(value)?))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            name: name.trim().to_string(),
+            lsb,
+            width: msb - lsb + 1,
+            values,
+        })
+    }
+
+    pub fn mask(&self) -> u32 {
+        (u32::MAX >> (32 - self.width as u32)) << self.lsb
+    }
+
+    pub fn extract(&self, value: u32) -> u32 {
+        (value & self.mask()) >> self.lsb
+    }
+
+    // 位域取值的显示，命名的取值显示名称，例如 "MODE=FAST"
+    pub fn describe(&self, value: u32) -> String {
+        let field_value = self.extract(value);
+        match self.values.iter().find(|(_, value)| *value == field_value) {
+            Some((label, _)) => format!("{}={}", self.name, label),
+            None => format!("{}={}", self.name, field_value),
+        }
+    }
+}
+
+// 寄存器表中一个寄存器的定义
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegisterDef {
+    pub address: u16,
+    pub name: String,
+    pub width: RegisterWidth,
+    pub access: Access,
+    // 复位值
+    pub reset: Option<u32>,
+    // 可写的位，未指定时为全部位
+    pub write_mask: u32,
+    pub fields: Vec<BitField>,
+}
+
+impl RegisterDef {
+    // 由 CSV 各列的文本构造，可选列为空时使用默认值
+    pub fn parse(
+        address: &str,
+        name: &str,
+        access: &str,
+        width: Option<&str>,
+        reset: Option<&str>,
+        write_mask: Option<&str>,
+        fields: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let address = parse_number(address)?;
+        let address =
+            u16::try_from(address).map_err(|_| anyhow!("页地址超出范围: 0x{:X}", address))?;
+        let width = RegisterWidth::parse(width.unwrap_or_default())?;
+
+        let mut register = Self {
+            address,
+            name: name.to_string(),
+            width,
+            access: Access::parse(access)?,
+            reset: None,
+            write_mask: width.full_mask(),
+            fields: Vec::new(),
+        };
+
+        if let Some(reset) = non_empty(reset) {
+            register.reset = Some(register.parse_value(reset)?);
+        }
+        if let Some(write_mask) = non_empty(write_mask) {
+            register.write_mask = register.parse_value(write_mask)?;
+        }
+        if let Some(fields) = non_empty(fields) {
+            register.fields = fields
+                .split(';')
+                .filter(|field| !field.trim().is_empty())
+                .map(BitField::parse)
+                .collect::<anyhow::Result<_>>()?;
+            if let Some(field) = register
+                .fields
+                .iter()
+                .find(|field| field.mask() & !width.full_mask() != 0)
+            {
+                return Err(anyhow!("位域 {} 超出寄存器宽度", field.name));
+            }
+        }
+
+        Ok(register)
+    }
+
+    // 解析寄存器值，超出寄存器宽度时报错
+    pub fn parse_value(&self, text: &str) -> anyhow::Result<u32> {
+        let value = parse_number(text)?;
+        if value & !self.width.full_mask() != 0 {
+            return Err(anyhow!(
+                "值 {} 超出 {} 位寄存器 {} 的范围",
+                text.trim(),
+                self.width.bits(),
+                self.name
+            ));
+        }
+        Ok(value)
+    }
+
+    pub fn format_value(&self, value: u32) -> String {
+        self.width.format(value)
+    }
+
+    // 占用的 Modbus 寄存器地址
+    pub fn addresses(&self) -> Vec<u16> {
+        (0..self.width.word_count())
+            .map(|offset| self.address.wrapping_add(offset))
+            .collect()
+    }
+
+    // 拆分为 Modbus 寄存器的值，高字在前
+    pub fn split_words(&self, value: u32) -> Vec<u16> {
+        match self.width {
+            RegisterWidth::W32 => vec![(value >> 16) as u16, value as u16],
+            _ => vec![value as u16],
+        }
+    }
+
+    // 由 Modbus 寄存器的值合成寄存器值，高字在前
+    pub fn join_words(&self, words: &[u16]) -> u32 {
+        words
+            .iter()
+            .fold(0u32, |value, &word| (value << 16) | word as u32)
+    }
+
+    // 按位域解释寄存器值，例如 "EN=1, MODE=FAST"；没有位域时为空
+    pub fn describe(&self, value: u32) -> String {
+        self.fields
+            .iter()
+            .map(|field| field.describe(value))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+fn non_empty(text: Option<&str>) -> Option<&str> {
+    text.map(str::trim).filter(|text| !text.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_register_def() {
+        let register = RegisterDef::parse(
+            "0x4010",
+            "CTRL",
+            "RW",
+            Some("16"),
+            Some("0x0100"),
+            Some("0x0FFF"),
+            Some("EN[0];MODE[2:1]=SLOW:0|FAST:1;GAIN[11:8]"),
+        )
+        .unwrap();
+
+        assert_eq!(register.address, 0x4010);
+        assert_eq!(register.width, RegisterWidth::W16);
+        assert_eq!(register.access, Access::ReadWrite);
+        assert_eq!(register.reset, Some(0x0100));
+        assert_eq!(register.write_mask, 0x0FFF);
+        assert_eq!(register.format_value(0x1C), "0x001C");
+        assert_eq!(register.describe(0x0303), "EN=1, MODE=FAST, GAIN=3");
+        assert!(register.parse_value("0x12345").is_err());
+
+        // 可选列为空时为 8 位、全部位可写
+        let register = RegisterDef::parse("16", "IO1", "w", None, Some(""), None, None).unwrap();
+        assert_eq!(register.width, RegisterWidth::W8);
+        assert_eq!(register.access, Access::WriteOnly);
+        assert_eq!(register.write_mask, 0xFF);
+        assert_eq!(register.reset, None);
+        // 超出宽度的读数完整显示
+        assert_eq!(register.format_value(0x1FF), "0x1FF");
+    }
+
+    #[test]
+    fn test_32_bit_words() {
+        let register =
+            RegisterDef::parse("0x4020", "COUNTER", "R", Some("32"), None, None, None).unwrap();
+        assert_eq!(register.addresses(), vec![0x4020, 0x4021]);
+        assert_eq!(register.split_words(0x1234_5678), vec![0x1234, 0x5678]);
+        assert_eq!(register.join_words(&[0x1234, 0x5678]), 0x1234_5678);
+        assert_eq!(register.format_value(0xAB), "0x000000AB");
+    }
+
+    #[test]
+    fn test_invalid_definitions() {
+        assert!(RegisterDef::parse("0x4000", "X", "RX", None, None, None, None).is_err());
+        assert!(RegisterDef::parse("0x4000", "X", "R", Some("12"), None, None, None).is_err());
+        assert!(RegisterDef::parse("0x10000", "X", "R", None, None, None, None).is_err());
+        // 位域超出 8 位寄存器
+        assert!(RegisterDef::parse("0x4000", "X", "R", None, None, None, Some("HI[9:8]")).is_err());
+        assert!(
+            RegisterDef::parse("0x4000", "X", "R", None, None, None, Some("BAD[1:2]")).is_err()
+        );
+    }
+}
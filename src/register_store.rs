@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+
+use crate::csv_handler::RegisterRecord;
+
+// 寄存器表中一行的键，同一地址可以有多行 (例如同一寄存器的不同位域)，按名称区分
+// 按数值地址排序，同一地址内按名称排序
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RegisterKey {
+    pub address: u16,
+    pub name: String,
+}
+
+impl RegisterKey {
+    pub fn new(address: u16, name: &str) -> Self {
+        Self {
+            address,
+            name: name.to_string(),
+        }
+    }
+
+    pub fn of(record: &RegisterRecord) -> Self {
+        Self::new(record.def.address, &record.register)
+    }
+}
+
+impl std::fmt::Display for RegisterKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0x{:04X}:{}", self.address, self.name)
+    }
+}
+
+// 载入的寄存器表，按地址 + 名称保存每一行
+#[derive(Debug, Default)]
+pub struct RegisterStore {
+    rows: BTreeMap<RegisterKey, RegisterRecord>,
+}
+
+impl RegisterStore {
+    // 加入一行，地址和名称都相同的行被替换，返回被替换的行
+    pub fn insert(&mut self, record: RegisterRecord) -> Option<RegisterRecord> {
+        self.rows.insert(RegisterKey::of(&record), record)
+    }
+
+    pub fn get(&self, key: &RegisterKey) -> Option<&RegisterRecord> {
+        self.rows.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &RegisterKey) -> Option<&mut RegisterRecord> {
+        self.rows.get_mut(key)
+    }
+
+    // 同一地址的所有行，按名称排序
+    pub fn rows_at(&self, address: u16) -> impl Iterator<Item = &RegisterRecord> {
+        self.rows
+            .range(RegisterKey::new(address, "")..)
+            .take_while(move |(key, _)| key.address == address)
+            .map(|(_, record)| record)
+    }
+
+    pub fn rows_at_mut(&mut self, address: u16) -> impl Iterator<Item = &mut RegisterRecord> {
+        self.rows
+            .range_mut(RegisterKey::new(address, "")..)
+            .take_while(move |(key, _)| key.address == address)
+            .map(|(_, record)| record)
+    }
+
+    // 所有行，按地址排序
+    pub fn records(&self) -> impl Iterator<Item = &RegisterRecord> {
+        self.rows.values()
+    }
+
+    pub fn records_mut(&mut self) -> impl Iterator<Item = &mut RegisterRecord> {
+        self.rows.values_mut()
+    }
+
+    // 有记录的地址，按数值排序
+    pub fn addresses(&self) -> Vec<u16> {
+        let mut addresses: Vec<u16> = self.rows.keys().map(|key| key.address).collect();
+        addresses.dedup();
+        addresses
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(page_addr: &str, register: &str) -> RegisterRecord {
+        RegisterRecord::new(
+            page_addr.to_string(),
+            register.to_string(),
+            "RW".to_string(),
+            "0x00".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_rows_sharing_address() {
+        let mut store = RegisterStore::default();
+        // 字符串排序时 "0x1000" 在 "0x900" 之前，十进制地址也按数值排序
+        for (page_addr, register) in [
+            ("0x1000", "CTRL"),
+            ("0x900", "MODE"),
+            ("0x900", "GAIN"),
+            ("16", "IO1"),
+        ] {
+            assert!(store.insert(record(page_addr, register)).is_none());
+        }
+        assert!(store.insert(record("0x0900", "GAIN")).is_some());
+
+        let order: Vec<&str> = store
+            .records()
+            .map(|record| record.register.as_str())
+            .collect();
+        assert_eq!(order, vec!["IO1", "GAIN", "MODE", "CTRL"]);
+        assert_eq!(store.len(), 4);
+        assert_eq!(store.addresses(), vec![0x0010, 0x0900, 0x1000]);
+
+        let names: Vec<&str> = store
+            .rows_at(0x0900)
+            .map(|record| record.register.as_str())
+            .collect();
+        assert_eq!(names, vec!["GAIN", "MODE"]);
+        assert_eq!(store.rows_at(0x0901).count(), 0);
+
+        let key = RegisterKey::new(0x0900, "MODE");
+        store.get_mut(&key).unwrap().w_value = Some("0x01".to_string());
+        assert_eq!(store.get(&key).unwrap().w_value.as_deref(), Some("0x01"));
+        assert_eq!(key.to_string(), "0x0900:MODE");
+    }
+}
@@ -5,8 +5,17 @@ use tokio::sync::{Mutex, OnceCell, mpsc};
 use tokio_util::sync::CancellationToken;
 
 use crate::serial::base::SerialPortManager;
+use crate::serial::modbus::Framing;
+use crate::serial::probe::probe_serial_port;
+use crate::serial::retry::RetryPolicy;
+use crate::serial::scheduler::{Priority, TransactionScheduler};
+use crate::serial::settings::SerialSettings;
+use crate::serial::simulator::DeviceSimulator;
+use crate::serial::tcp::{ModbusTcpClient, RtuOverTcpClient};
+use crate::serial::transport::ModbusTransport;
 
 // 定义串口事件类型
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone)]
 pub enum SerialPortEvent {
     /// 串口已添加到监听列表
@@ -34,13 +43,23 @@ pub type SerialEventReceiver = mpsc::UnboundedReceiver<SerialPortEvent>;
 pub struct SerialPortRegistry {
     // 使用 HashMap 存储 SerialPortManager 实例，键为串口路径
     ports: Mutex<HashMap<String, Arc<SerialPortManager>>>,
+    // 串口以外的传输层 (Modbus TCP / RTU over TCP / 模拟设备)，键为连接标识
+    transports: Mutex<HashMap<String, Arc<dyn ModbusTransport>>>,
+    // 每个连接的事务调度器，首次使用时创建
+    schedulers: Mutex<HashMap<String, Arc<TransactionScheduler>>>,
     // 注册表级别的取消令牌，用于通知所有管理的串口管理器及其任务退出
     registry_cancel_token: CancellationToken,
     task_ports: Mutex<Vec<String>>, // 新增
+    // 每个串口最近一次使用的线路参数，串口重新插入时沿用
+    port_settings: Mutex<HashMap<String, SerialSettings>>,
+    // 每个串口最近一次使用的帧格式，串口重新插入时沿用
+    port_framings: Mutex<HashMap<String, Framing>>,
     // 新增默认参数
-    default_baud_rate: u32,
-    default_read_timeout_ms: u64,
+    default_serial_settings: SerialSettings,
     default_data_channel_buffer_size: usize,
+    default_connect_timeout_ms: u64,
+    // 读写寄存器时使用的重试策略
+    retry_policy: std::sync::RwLock<RetryPolicy>,
     // 事件发送器列表 - 支持多个订阅者
     event_senders: Mutex<Vec<SerialEventSender>>,
 }
@@ -53,11 +72,16 @@ impl SerialPortRegistry {
     pub fn new() -> Arc<Self> {
         let registry = Arc::new(Self {
             ports: Mutex::new(HashMap::new()),
+            transports: Mutex::new(HashMap::new()),
+            schedulers: Mutex::new(HashMap::new()),
             registry_cancel_token: CancellationToken::new(),
             task_ports: Mutex::new(Vec::new()), // 新增
-            default_baud_rate: 115200,
-            default_read_timeout_ms: 200,
+            port_settings: Mutex::new(HashMap::new()),
+            port_framings: Mutex::new(HashMap::new()),
+            default_serial_settings: SerialSettings::default(),
             default_data_channel_buffer_size: 8,
+            default_connect_timeout_ms: 3000,
+            retry_policy: std::sync::RwLock::new(RetryPolicy::default()),
             event_senders: Mutex::new(Vec::new()),
         });
 
@@ -187,8 +211,10 @@ impl SerialPortRegistry {
                             "监测任务: 端口 {} 在系统和监测列表中, 但未注册, 自动添加",
                             port
                         );
-                        // 这里需要指定默认参数, 你可以根据实际情况调整
-                        match registry.add_scpi_port(port, 115200).await {
+                        // 沿用该串口上次的线路参数和帧格式，没有则使用默认值
+                        let settings = registry.get_port_settings(port).await;
+                        let framing = registry.get_port_framing(port).await;
+                        match registry.add_scpi_port(port, settings, framing).await {
                             Ok(_) => {
                                 // 触发重新连接事件
                                 registry
@@ -232,24 +258,53 @@ impl SerialPortRegistry {
         });
     }
 
-    // 新增：带默认参数的 add_port
-    pub async fn add_port_with_defaults(&self, port_path: &str) -> anyhow::Result<()> {
+    // 新增：使用默认通道缓冲区大小的 add_port
+    pub async fn add_port_with_defaults(
+        &self,
+        port_path: &str,
+        settings: SerialSettings,
+        framing: Framing,
+    ) -> anyhow::Result<()> {
         self.add_port(
             port_path,
-            self.default_baud_rate,
-            self.default_read_timeout_ms,
+            settings,
+            framing,
             self.default_data_channel_buffer_size,
         )
         .await
     }
 
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy.read().unwrap().clone()
+    }
+
+    pub fn set_retry_policy(&self, policy: RetryPolicy) {
+        log::info!("重试策略: {:?}", policy);
+        *self.retry_policy.write().unwrap() = policy;
+    }
+
+    // 获取串口最近一次使用的线路参数，没有记录时返回默认参数
+    pub async fn get_port_settings(&self, port_path: &str) -> SerialSettings {
+        let port_settings = self.port_settings.lock().await;
+        port_settings
+            .get(port_path)
+            .copied()
+            .unwrap_or(self.default_serial_settings)
+    }
+
+    // 获取串口最近一次使用的帧格式，没有记录时返回 RTU
+    pub async fn get_port_framing(&self, port_path: &str) -> Framing {
+        let port_framings = self.port_framings.lock().await;
+        port_framings.get(port_path).copied().unwrap_or_default()
+    }
+
     // 添加并启动一个新的串口管理器
     // data_channel_buffer_size: 内部数据通道的缓冲区大小
     pub async fn add_port(
         &self,
         port_path: &str,
-        baud_rate: u32,
-        read_timeout_ms: u64,
+        settings: SerialSettings,
+        framing: Framing,
         data_channel_buffer_size: usize,
     ) -> anyhow::Result<()> {
         let mut ports = self.ports.lock().await;
@@ -266,8 +321,8 @@ impl SerialPortRegistry {
         // SerialPortManager::new 内部会创建自己的通道并启动接收和数据处理任务
         let port_manager = SerialPortManager::new(
             port_path,
-            baud_rate,
-            read_timeout_ms,
+            settings,
+            framing,
             port_cancel_token,        // 传递子令牌
             data_channel_buffer_size, // 传递内部通道缓冲区大小
             true,                     // 启用自动接收任务 (传统模式)
@@ -275,13 +330,30 @@ impl SerialPortRegistry {
 
         // 存储管理器
         ports.insert(port_path.to_string(), Arc::clone(&port_manager));
+        self.port_settings
+            .lock()
+            .await
+            .insert(port_path.to_string(), settings);
+        self.port_framings
+            .lock()
+            .await
+            .insert(port_path.to_string(), framing);
 
-        log::info!("串口 {} 已添加到注册表并启动内部任务", port_path);
+        log::info!(
+            "串口 {} ({}) 已添加到注册表并启动内部任务",
+            port_path,
+            settings
+        );
         Ok(())
     }
 
     // SCPI通信专用的端口添加方法 - 禁用自动接收任务
-    pub async fn add_scpi_port(&self, port_path: &str, baud_rate: u32) -> anyhow::Result<()> {
+    pub async fn add_scpi_port(
+        &self,
+        port_path: &str,
+        settings: SerialSettings,
+        framing: Framing,
+    ) -> anyhow::Result<()> {
         let mut ports = self.ports.lock().await;
         if ports.contains_key(port_path) {
             log::warn!("SCPI串口 {} 已存在于注册表中", port_path);
@@ -292,15 +364,84 @@ impl SerialPortRegistry {
         let port_cancel_token = self.registry_cancel_token.child_token();
 
         // 使用SCPI专用构造函数创建管理器
-        let port_manager = SerialPortManager::new_for_scpi(port_path, baud_rate, port_cancel_token);
+        let port_manager =
+            SerialPortManager::new_for_scpi(port_path, settings, framing, port_cancel_token);
 
         // 存储管理器
         ports.insert(port_path.to_string(), Arc::clone(&port_manager));
+        self.port_settings
+            .lock()
+            .await
+            .insert(port_path.to_string(), settings);
+        self.port_framings
+            .lock()
+            .await
+            .insert(port_path.to_string(), framing);
 
         log::info!("SCPI串口 {} 已添加到注册表 (禁用自动接收任务)", port_path);
         Ok(())
     }
 
+    // 探测未知板卡的波特率和校验方式，找到后按该参数添加串口并记录
+    // 探测期间需要独占串口，已添加的串口需先断开
+    pub async fn probe_port(
+        &self,
+        port_path: &str,
+        base: SerialSettings,
+        framing: Framing,
+    ) -> anyhow::Result<SerialSettings> {
+        if self.get_port(port_path).await.is_some() {
+            return Err(anyhow::anyhow!("串口 {} 已连接，请先断开再探测", port_path));
+        }
+
+        let settings =
+            probe_serial_port(port_path, base, framing, &self.registry_cancel_token).await?;
+        self.add_port_with_defaults(port_path, settings, framing)
+            .await?;
+        Ok(settings)
+    }
+
+    // 添加 Modbus TCP 连接，address 为 host:port，timeout_ms 为响应超时时间
+    pub async fn add_tcp_port(&self, address: &str, timeout_ms: u64) -> anyhow::Result<()> {
+        self.add_transport(ModbusTcpClient::new(
+            address,
+            self.default_connect_timeout_ms,
+            timeout_ms,
+        ))
+        .await
+    }
+
+    // 添加 RTU over TCP 连接 (串口服务器透传)，address 为 host:port，timeout_ms 为响应超时时间
+    pub async fn add_tcp_rtu_port(&self, address: &str, timeout_ms: u64) -> anyhow::Result<()> {
+        self.add_transport(RtuOverTcpClient::new(
+            address,
+            self.default_connect_timeout_ms,
+            timeout_ms,
+        ))
+        .await
+    }
+
+    // 添加进程内设备模拟器，url 为 "sim://" 加可选的寄存器表 CSV 路径
+    pub async fn add_simulator_port(&self, url: &str) -> anyhow::Result<()> {
+        let simulator = DeviceSimulator::from_url(url)?;
+        self.add_transport(simulator.transport()).await
+    }
+
+    // 添加任意传输层连接，以传输层的连接标识作为键
+    pub async fn add_transport(&self, transport: Arc<dyn ModbusTransport>) -> anyhow::Result<()> {
+        let key = transport.get_port().to_string();
+        let mut transports = self.transports.lock().await;
+        if transports.contains_key(&key) {
+            log::warn!("连接 {} 已存在于注册表中", key);
+            return Err(anyhow::anyhow!("连接 {} 已存在", key));
+        }
+
+        transports.insert(key.clone(), transport);
+
+        log::info!("连接 {} 已添加到注册表", key);
+        Ok(())
+    }
+
     // 打开所有注册的串口
     pub async fn open_all(&self) {
         let ports = self.ports.lock().await;
@@ -320,6 +461,14 @@ impl SerialPortRegistry {
 
         // 等待所有打开任务完成 (可选，取决于需求)
         join_all(open_futures).await;
+        drop(ports);
+
+        let transports = self.transports.lock().await;
+        for transport in transports.values().filter(|transport| !transport.is_open()) {
+            if let Err(e) = transport.open().await {
+                log::error!("打开连接 {} 失败: {}", transport.get_port(), e);
+            }
+        }
     }
 
     // 关闭所有注册的串口并停止相关任务
@@ -347,6 +496,11 @@ impl SerialPortRegistry {
         //等待所有关闭任务完成 (可选)
         join_all(close_futures).await;
 
+        let transports = self.transports.lock().await;
+        for transport in transports.values() {
+            transport.close().await;
+        }
+
         log::info!("所有注册串口已关闭");
     }
 
@@ -386,22 +540,62 @@ impl SerialPortRegistry {
         ports.get(port_path).cloned() // cloned() 创建一个新的 Arc 引用
     }
 
-    pub async fn is_connected(&self, port_path: &str) -> bool {
+    // 获取指定连接的 Modbus 传输层，串口和网络连接都可获取
+    pub async fn get_transport(&self, port_path: &str) -> Option<Arc<dyn ModbusTransport>> {
         if let Some(manager) = self.get_port(port_path).await {
-            manager.is_open()
+            return Some(manager);
+        }
+
+        let transports = self.transports.lock().await;
+        transports.get(port_path).cloned()
+    }
+
+    // 获取连接的事务调度器，连接不存在时返回 None
+    pub async fn get_scheduler(&self, port_path: &str) -> Option<Arc<TransactionScheduler>> {
+        let transport = self.get_transport(port_path).await?;
+        let mut schedulers = self.schedulers.lock().await;
+        let scheduler = schedulers
+            .entry(port_path.to_string())
+            .or_insert_with(|| TransactionScheduler::new(transport));
+        Some(Arc::clone(scheduler))
+    }
+
+    // 以指定优先级获取连接的传输层，事务经该连接的调度器排队执行
+    pub async fn get_scheduled_transport(
+        &self,
+        port_path: &str,
+        priority: Priority,
+    ) -> Option<Arc<dyn ModbusTransport>> {
+        let scheduler = self.get_scheduler(port_path).await?;
+        Some(scheduler.transport(priority))
+    }
+
+    pub async fn is_connected(&self, port_path: &str) -> bool {
+        if let Some(transport) = self.get_transport(port_path).await {
+            transport.is_open()
         } else {
             false
         }
     }
 
     // 移除前会先关闭该串口并触发其任务的取消
-    pub async fn remove_port(&self, port_path: &str) -> Option<Arc<SerialPortManager>> {
+    pub async fn remove_port(&self, port_path: &str) -> Option<Arc<dyn ModbusTransport>> {
+        self.schedulers.lock().await.remove(port_path);
+
         let mut ports = self.ports.lock().await;
         if let Some(manager) = ports.remove(port_path) {
             log::info!("从注册表移除串口 {}", port_path);
             // 触发该串口管理器的取消令牌，通知其任务永久退出
             manager.cancel_tasks().await;
-            Some(manager)
+            return Some(manager);
+        }
+        drop(ports);
+
+        let mut transports = self.transports.lock().await;
+        if let Some(transport) = transports.remove(port_path) {
+            log::info!("从注册表移除连接 {}", port_path);
+            transport.close().await;
+            Some(transport)
         } else {
             None
         }
@@ -418,8 +612,123 @@ impl SerialPortRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::serial::mock::MockTransport;
+    use crate::serial::rs485::Rs485Config;
+
+    #[tokio::test]
+    async fn test_registry_with_mock_transport() {
+        let registry = SerialPortRegistry::new();
+        let mock =
+            Arc::new(MockTransport::new("mock://chip", 0x01).with_registers([(0x4000, 0x1C)]));
+        registry.add_transport(mock.clone()).await.unwrap();
+
+        // 同名连接不能重复添加
+        assert!(registry.add_transport(mock.clone()).await.is_err());
+        assert!(!registry.is_connected("mock://chip").await);
+
+        registry.open_all().await;
+        assert!(registry.is_connected("mock://chip").await);
+
+        let transport = registry.get_transport("mock://chip").await.unwrap();
+        assert_eq!(
+            transport.read_registers(0x01, 0x4000, 1).await.unwrap(),
+            vec![0x1C]
+        );
 
+        // 经调度器排队的事务
+        let scheduled = registry
+            .get_scheduled_transport("mock://chip", Priority::Interactive)
+            .await
+            .unwrap();
+        assert_eq!(
+            scheduled.read_registers(0x01, 0x4000, 1).await.unwrap(),
+            vec![0x1C]
+        );
+        let scheduler = registry.get_scheduler("mock://chip").await.unwrap();
+        assert_eq!(scheduler.queue_depth().total(), 0);
+
+        assert!(registry.remove_port("mock://chip").await.is_some());
+        assert!(registry.get_scheduler("mock://chip").await.is_none());
+        assert!(!mock.is_open());
+        assert!(registry.get_transport("mock://chip").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_port_settings_remembered() {
+        let registry = SerialPortRegistry::new();
+        let settings =
+            SerialSettings::parse("9600", "8", "E", "1", "无", "500", Rs485Config::default())
+                .unwrap();
+
+        // 添加时不打开串口，只记录线路参数和帧格式
+        registry
+            .add_scpi_port("/dev/ttyMCU0", settings, Framing::Ascii)
+            .await
+            .unwrap();
+        assert_eq!(registry.get_port_settings("/dev/ttyMCU0").await, settings);
+        assert_eq!(
+            registry.get_port_framing("/dev/ttyMCU0").await,
+            Framing::Ascii
+        );
+        assert_eq!(
+            registry.get_port("/dev/ttyMCU0").await.unwrap().settings(),
+            settings
+        );
+
+        // 移除后重新插入时沿用上次的参数和帧格式
+        registry.remove_port("/dev/ttyMCU0").await;
+        assert_eq!(registry.get_port_settings("/dev/ttyMCU0").await, settings);
+        assert_eq!(
+            registry.get_port_framing("/dev/ttyMCU0").await,
+            Framing::Ascii
+        );
+        assert_eq!(
+            registry.get_port_settings("/dev/ttyMCU1").await,
+            SerialSettings::default()
+        );
+        assert_eq!(
+            registry.get_port_framing("/dev/ttyMCU1").await,
+            Framing::Rtu
+        );
+    }
+
+    #[cfg(target_os = "linux")]
     #[tokio::test]
+    async fn test_probe_port_over_pty() {
+        use crate::serial::simulator::{DeviceSimulator, MALD_CHIP_ID, MATA_CHIP_ID};
+
+        let simulator = Arc::new(DeviceSimulator::new("sim://", MALD_CHIP_ID, MATA_CHIP_ID));
+        let cancel_token = CancellationToken::new();
+        let path = simulator.spawn_pty(cancel_token.clone()).unwrap();
+
+        let registry = SerialPortRegistry::new();
+        let base =
+            SerialSettings::parse("9600", "8", "E", "1", "无", "200", Rs485Config::default())
+                .unwrap();
+        let settings = registry
+            .probe_port(&path, base, Framing::Rtu)
+            .await
+            .unwrap();
+
+        // 伪终端不区分波特率，第一组参数即可应答
+        assert_eq!(settings, base);
+        assert_eq!(registry.get_port_settings(&path).await, base);
+        assert!(registry.get_port(&path).await.is_some());
+
+        // 已添加的串口不能再次探测
+        assert!(
+            registry
+                .probe_port(&path, base, Framing::Rtu)
+                .await
+                .is_err()
+        );
+
+        registry.remove_port(&path).await;
+        cancel_token.cancel();
+    }
+
+    #[tokio::test]
+    #[ignore = "需要连接实际串口"]
     async fn test_name() {
         crate::config::init_config();
         let registry = SerialPortRegistry::get_global().await;
@@ -427,7 +736,17 @@ mod tests {
         // registry.add_task_port("COM2").await;
         registry.add_task_port("COM3").await;
         // registry.add_task_port("COM4").await;
-        let _ = registry.add_port("COM1", 256000, 200, 8).await;
+        let _ = registry
+            .add_port(
+                "COM1",
+                SerialSettings {
+                    baud_rate: 256000,
+                    ..Default::default()
+                },
+                Framing::Rtu,
+                8,
+            )
+            .await;
 
         registry.open_all().await;
 
@@ -0,0 +1,295 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::serial::modbus::{ModbusError, ModbusException, ModbusFrame, ModbusTransaction};
+use crate::serial::transport::ModbusTransport;
+
+// 内存中的模拟 Modbus 从站
+// 不依赖任何硬件，按寄存器表应答读写请求，用于在 CI 中端到端测试
+// 芯片检测、CSV 读写和 IO 轮询等上层逻辑
+pub struct MockTransport {
+    port_name: String,
+    slave_address: u8,
+    is_connected: AtomicBool,
+    // 寄存器表，未定义的地址按非法数据地址异常应答
+    registers: Mutex<HashMap<u16, u16>>,
+    // 收到的请求记录，便于测试校验分块和功能码
+    requests: Mutex<Vec<ModbusFrame>>,
+    // 之后这么多个请求不应答，模拟线路干扰
+    dropped_requests: AtomicUsize,
+}
+
+impl MockTransport {
+    pub fn new(port_name: &str, slave_address: u8) -> Self {
+        Self {
+            port_name: port_name.to_string(),
+            slave_address,
+            is_connected: AtomicBool::new(false),
+            registers: Mutex::new(HashMap::new()),
+            requests: Mutex::new(Vec::new()),
+            dropped_requests: AtomicUsize::new(0),
+        }
+    }
+
+    // 以给定的寄存器表初始化
+    pub fn with_registers(self, registers: impl IntoIterator<Item = (u16, u16)>) -> Self {
+        self.registers.lock().unwrap().extend(registers);
+        self
+    }
+
+    pub fn set_register(&self, address: u16, value: u16) {
+        self.registers.lock().unwrap().insert(address, value);
+    }
+
+    pub fn register(&self, address: u16) -> Option<u16> {
+        self.registers.lock().unwrap().get(&address).copied()
+    }
+
+    // 之后的 count 个请求按超时处理
+    pub fn drop_requests(&self, count: usize) {
+        self.dropped_requests.store(count, Ordering::SeqCst);
+    }
+
+    pub fn slave_address(&self) -> u8 {
+        self.slave_address
+    }
+
+    // 已收到的全部请求
+    pub fn requests(&self) -> Vec<ModbusFrame> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    // 按从站逻辑处理一个请求，返回应答 PDU (功能码 + 数据)
+    pub fn handle_request(&self, request: &ModbusFrame) -> Vec<u8> {
+        let function_code = request.get_function_code();
+        let data = request.get_data();
+
+        match self.apply_request(function_code, data) {
+            Ok(response) => {
+                let mut pdu = vec![function_code];
+                pdu.extend(response);
+                pdu
+            }
+            Err(exception) => vec![function_code | 0x80, exception.code()],
+        }
+    }
+
+    fn apply_request(&self, function_code: u8, data: &[u8]) -> Result<Vec<u8>, ModbusException> {
+        if data.len() < 4 {
+            return Err(ModbusException::IllegalDataValue);
+        }
+        let start = u16::from_be_bytes([data[0], data[1]]);
+        let quantity = u16::from_be_bytes([data[2], data[3]]);
+        let mut registers = self.registers.lock().unwrap();
+
+        match function_code {
+            // 读保持寄存器 / 读输入寄存器
+            0x03 | 0x04 => {
+                if quantity == 0 || quantity > 125 {
+                    return Err(ModbusException::IllegalDataValue);
+                }
+                let mut response = vec![(quantity * 2) as u8];
+                for offset in 0..quantity {
+                    let address = start.wrapping_add(offset);
+                    let value = registers
+                        .get(&address)
+                        .ok_or(ModbusException::IllegalDataAddress)?;
+                    response.extend_from_slice(&value.to_be_bytes());
+                }
+                Ok(response)
+            }
+            // 写单个寄存器，回显请求
+            0x06 => {
+                if !registers.contains_key(&start) {
+                    return Err(ModbusException::IllegalDataAddress);
+                }
+                registers.insert(start, quantity);
+                Ok(data[..4].to_vec())
+            }
+            // 写多个寄存器，回显起始地址和数量
+            0x10 => {
+                let values = data.get(5..).unwrap_or_default();
+                if quantity == 0 || values.len() != quantity as usize * 2 {
+                    return Err(ModbusException::IllegalDataValue);
+                }
+                let addresses: Vec<u16> = (0..quantity).map(|i| start.wrapping_add(i)).collect();
+                if addresses
+                    .iter()
+                    .any(|address| !registers.contains_key(address))
+                {
+                    return Err(ModbusException::IllegalDataAddress);
+                }
+                for (address, value) in addresses.into_iter().zip(values.chunks_exact(2)) {
+                    registers.insert(address, u16::from_be_bytes([value[0], value[1]]));
+                }
+                Ok(data[..4].to_vec())
+            }
+            _ => Err(ModbusException::IllegalFunction),
+        }
+    }
+}
+
+#[async_trait]
+impl ModbusTransport for MockTransport {
+    fn get_port(&self) -> &str {
+        &self.port_name
+    }
+
+    fn is_open(&self) -> bool {
+        self.is_connected.load(Ordering::SeqCst)
+    }
+
+    async fn open(&self) -> anyhow::Result<()> {
+        self.is_connected.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn close(&self) {
+        self.is_connected.store(false, Ordering::SeqCst);
+    }
+
+    // 应答经过完整的 RTU 编码和校验，与串口路径一致
+    async fn transact(
+        &self,
+        transaction: ModbusTransaction,
+        _timeout_ms: u64,
+    ) -> anyhow::Result<ModbusFrame> {
+        if !self.is_open() {
+            return Err(anyhow::anyhow!("Serial port not open"));
+        }
+
+        let request = transaction.request();
+        self.requests.lock().unwrap().push(request.clone());
+
+        // 其他从站地址无应答
+        if request.get_slave_address() != self.slave_address {
+            return Err(ModbusError::Timeout.into());
+        }
+        if self
+            .dropped_requests
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                count.checked_sub(1)
+            })
+            .is_ok()
+        {
+            return Err(ModbusError::Timeout.into());
+        }
+
+        let pdu = self.handle_request(request);
+        let response = ModbusFrame::new(self.slave_address, pdu[0], pdu[1..].to_vec());
+        Ok(transaction.parse_response(&response.to_bytes())?)
+    }
+}
+
+// 内存中的模拟多点总线，按请求的从站地址转发给对应的模拟从站
+// 总线上没有的地址无应答，用于测试总线扫描
+pub struct MockBus {
+    port_name: String,
+    slaves: Vec<MockTransport>,
+    is_connected: AtomicBool,
+}
+
+impl MockBus {
+    pub fn new(port_name: &str, slaves: impl IntoIterator<Item = MockTransport>) -> Self {
+        Self {
+            port_name: port_name.to_string(),
+            slaves: slaves.into_iter().collect(),
+            is_connected: AtomicBool::new(false),
+        }
+    }
+}
+
+#[async_trait]
+impl ModbusTransport for MockBus {
+    fn get_port(&self) -> &str {
+        &self.port_name
+    }
+
+    fn is_open(&self) -> bool {
+        self.is_connected.load(Ordering::SeqCst)
+    }
+
+    async fn open(&self) -> anyhow::Result<()> {
+        for slave in &self.slaves {
+            slave.open().await?;
+        }
+        self.is_connected.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn close(&self) {
+        for slave in &self.slaves {
+            slave.close().await;
+        }
+        self.is_connected.store(false, Ordering::SeqCst);
+    }
+
+    async fn transact(
+        &self,
+        transaction: ModbusTransaction,
+        timeout_ms: u64,
+    ) -> anyhow::Result<ModbusFrame> {
+        if !self.is_open() {
+            return Err(anyhow::anyhow!("Serial port not open"));
+        }
+
+        let slave_address = transaction.request().get_slave_address();
+        match self
+            .slaves
+            .iter()
+            .find(|slave| slave.slave_address() == slave_address)
+        {
+            Some(slave) => slave.transact(transaction, timeout_ms).await,
+            None => Err(ModbusError::Timeout.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::modbus::ModbusError;
+
+    #[tokio::test]
+    async fn test_mock_read_write() {
+        let mock =
+            MockTransport::new("mock", 0x01).with_registers([(0x1000, 0x12), (0x1001, 0x34)]);
+        mock.open().await.unwrap();
+
+        assert_eq!(
+            mock.read_registers(0x01, 0x1000, 2).await.unwrap(),
+            vec![0x12, 0x34]
+        );
+
+        mock.write_registers(0x01, 0x1000, &[0x56, 0x78])
+            .await
+            .unwrap();
+        mock.write_register(0x01, 0x1001, 0x9A).await.unwrap();
+        assert_eq!(mock.register(0x1000), Some(0x56));
+        assert_eq!(mock.register(0x1001), Some(0x9A));
+        assert_eq!(mock.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_mock_errors() {
+        let mock = MockTransport::new("mock", 0x01).with_registers([(0x1000, 0x12)]);
+
+        // 未打开时拒绝请求
+        assert!(mock.read_registers(0x01, 0x1000, 1).await.is_err());
+        mock.open().await.unwrap();
+
+        // 未定义的地址返回异常响应
+        let error = mock.read_registers(0x01, 0x1000, 2).await.unwrap_err();
+        assert_eq!(
+            error
+                .downcast_ref::<ModbusError>()
+                .and_then(ModbusError::exception),
+            Some(ModbusException::IllegalDataAddress)
+        );
+
+        // 其他从站地址无应答
+        assert!(mock.read_registers(0x02, 0x1000, 1).await.is_err());
+    }
+}
@@ -1,3 +1,12 @@
 pub mod base;
 pub mod manager;
-pub mod modbus;
\ No newline at end of file
+pub mod mock;
+pub mod modbus;
+pub mod probe;
+pub mod retry;
+pub mod rs485;
+pub mod scheduler;
+pub mod settings;
+pub mod simulator;
+pub mod tcp;
+pub mod transport;
@@ -7,8 +7,11 @@ pub enum ModbusError {
     #[error("CRC校验失败: 计算值 {calculated:04X}, 接收值 {received:04X}")]
     CrcMismatch { calculated: u16, received: u16 },
 
-    #[error("Modbus异常响应: 功能码 0x{code:02X}, 错误码 0x{error:02X}")]
-    ExceptionResponse { code: u8, error: u8 },
+    #[error("Modbus异常响应: 功能码 0x{code:02X}, {exception}")]
+    ExceptionResponse {
+        code: u8,
+        exception: ModbusException,
+    },
 
     #[error("数据长度无效: 预期 {expected}, 实际 {actual}")]
     InvalidLength { expected: usize, actual: usize },
@@ -21,6 +24,133 @@ pub enum ModbusError {
 
     #[error("从站地址不匹配: 预期 {expected}, 实际 {actual}")]
     AddressMismatch { expected: u8, actual: u8 },
+
+    #[error("不支持写入该类型寄存器: {0:?}")]
+    UnsupportedWrite(RegisterType),
+
+    #[error("数量无效: {quantity} (范围 1-{max})")]
+    InvalidQuantity { quantity: usize, max: usize },
+
+    #[error("LRC校验失败: 计算值 {calculated:02X}, 接收值 {received:02X}")]
+    LrcMismatch { calculated: u8, received: u8 },
+
+    #[error("ASCII帧格式无效: {0}")]
+    InvalidAsciiFrame(String),
+
+    #[error("字节数不匹配: 预期 {expected}, 实际 {actual}")]
+    ByteCountMismatch { expected: usize, actual: usize },
+
+    #[error("写入回显不匹配: 请求 {request:02X?}, 响应 {response:02X?}")]
+    EchoMismatch { request: Vec<u8>, response: Vec<u8> },
+
+    #[error("Response timeout")]
+    Timeout,
+}
+
+impl ModbusError {
+    // 如果是从站返回的异常响应，获取异常类型
+    pub fn exception(&self) -> Option<ModbusException> {
+        match self {
+            Self::ExceptionResponse { exception, .. } => Some(*exception),
+            _ => None,
+        }
+    }
+}
+
+// 标准 Modbus 异常码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModbusException {
+    IllegalFunction,
+    IllegalDataAddress,
+    IllegalDataValue,
+    ServerDeviceFailure,
+    Acknowledge,
+    Busy,
+    GatewayPathUnavailable,
+    GatewayTargetFailed,
+    Unknown(u8),
+}
+
+impl ModbusException {
+    // 获取异常码数值
+    pub fn code(&self) -> u8 {
+        match self {
+            Self::IllegalFunction => 0x01,
+            Self::IllegalDataAddress => 0x02,
+            Self::IllegalDataValue => 0x03,
+            Self::ServerDeviceFailure => 0x04,
+            Self::Acknowledge => 0x05,
+            Self::Busy => 0x06,
+            Self::GatewayPathUnavailable => 0x0A,
+            Self::GatewayTargetFailed => 0x0B,
+            Self::Unknown(code) => *code,
+        }
+    }
+
+    // 获取异常的中文说明
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::IllegalFunction => "非法功能: 从站不支持该功能码",
+            Self::IllegalDataAddress => "非法数据地址: 寄存器地址不存在或超出范围",
+            Self::IllegalDataValue => "非法数据值: 从站不接受该写入值或数量",
+            Self::ServerDeviceFailure => "从站设备故障: 执行请求时发生不可恢复的错误",
+            Self::Acknowledge => "已确认: 从站已接受请求，需要较长时间处理",
+            Self::Busy => "从站设备忙: 请稍后重试",
+            Self::GatewayPathUnavailable => "网关路径不可用: 网关无法分配通信路径",
+            Self::GatewayTargetFailed => "网关目标设备无响应",
+            Self::Unknown(_) => "未知异常",
+        }
+    }
+}
+
+impl From<u8> for ModbusException {
+    fn from(code: u8) -> Self {
+        match code {
+            0x01 => Self::IllegalFunction,
+            0x02 => Self::IllegalDataAddress,
+            0x03 => Self::IllegalDataValue,
+            0x04 => Self::ServerDeviceFailure,
+            0x05 => Self::Acknowledge,
+            0x06 => Self::Busy,
+            0x0A => Self::GatewayPathUnavailable,
+            0x0B => Self::GatewayTargetFailed,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl std::fmt::Display for ModbusException {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (异常码 0x{:02X})", self.description(), self.code())
+    }
+}
+
+// 单次读保持/输入寄存器的最大数量 (协议限制)
+pub const MAX_READ_REGISTERS: u16 = 125;
+// 单次写多个寄存器 (0x10) 的最大数量 (协议限制)
+pub const MAX_WRITE_REGISTERS: u16 = 123;
+// 单次写多个线圈 (0x0F) 的最大数量 (协议限制)
+pub const MAX_WRITE_COILS: u16 = 1968;
+
+// Modbus 串行链路的帧格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Framing {
+    // 二进制帧，CRC-16 校验
+    #[default]
+    Rtu,
+    // ':' 起始、十六进制文本、LRC 校验、CRLF 结束
+    Ascii,
+}
+
+impl Framing {
+    // 从界面选择的文本解析帧格式，无法识别时使用 RTU
+    pub fn from_name(name: &str) -> Self {
+        if name.eq_ignore_ascii_case("ASCII") {
+            Self::Ascii
+        } else {
+            Self::Rtu
+        }
+    }
 }
 
 // 定义 Modbus 寄存器类型
@@ -42,17 +172,25 @@ impl RegisterType {
         }
     }
 
-    fn write_code(&self) -> u8 {
+    fn write_code(&self) -> Result<u8, ModbusError> {
         match self {
-            Self::Coil => 0x05,
-            Self::HoldingRegister => 0x06,
-            _ => panic!("不支持写入该类型寄存器"),
+            Self::Coil => Ok(0x05),
+            Self::HoldingRegister => Ok(0x06),
+            _ => Err(ModbusError::UnsupportedWrite(*self)),
+        }
+    }
+
+    fn write_multiple_code(&self) -> Result<u8, ModbusError> {
+        match self {
+            Self::Coil => Ok(0x0F),
+            Self::HoldingRegister => Ok(0x10),
+            _ => Err(ModbusError::UnsupportedWrite(*self)),
         }
     }
 }
 
 // Modbus-RTU 帧结构体
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ModbusFrame {
     slave_address: u8,
     function_code: u8,
@@ -61,6 +199,8 @@ pub struct ModbusFrame {
 
 impl ModbusFrame {
     const MIN_FRAME_LENGTH: usize = 4; // 地址+功能码+CRC
+    const EXCEPTION_FRAME_LENGTH: usize = 5; // 地址+功能码+异常码+CRC
+    const ECHO_FRAME_LENGTH: usize = 8; // 地址+功能码+地址(2)+值/数量(2)+CRC
 
     // 创建新帧
     pub fn new(slave_address: u8, function_code: u8, data: Vec<u8>) -> Self {
@@ -79,15 +219,78 @@ impl ModbusFrame {
         quantity: u16,
     ) -> Result<Self, ModbusError> {
         let function_code = register_type.read_code();
-        let mut data = Vec::with_capacity(4);
+        let data = vec![
+            // 起始地址（高字节在前）
+            (start_address >> 8) as u8,
+            (start_address & 0xFF) as u8,
+            // 数量（高字节在前）
+            (quantity >> 8) as u8,
+            (quantity & 0xFF) as u8,
+        ];
+
+        Ok(Self {
+            slave_address,
+            function_code,
+            data,
+        })
+    }
+    // 创建写单个保持寄存器请求帧 (功能码 0x06)
+    pub fn new_write_single_register(slave_address: u8, address: u16, value: u16) -> Self {
+        Self::new_write_single(slave_address, RegisterType::HoldingRegister, address, value)
+            .expect("保持寄存器支持单个写入")
+    }
+
+    // 创建写单个线圈请求帧 (功能码 0x05)
+    pub fn new_write_single_coil(slave_address: u8, address: u16, on: bool) -> Self {
+        let value = if on { 0xFF00 } else { 0x0000 };
+        Self::new_write_single(slave_address, RegisterType::Coil, address, value)
+            .expect("线圈支持单个写入")
+    }
+
+    fn new_write_single(
+        slave_address: u8,
+        register_type: RegisterType,
+        address: u16,
+        value: u16,
+    ) -> Result<Self, ModbusError> {
+        let function_code = register_type.write_code()?;
+        let data = vec![
+            (address >> 8) as u8,
+            (address & 0xFF) as u8,
+            (value >> 8) as u8,
+            (value & 0xFF) as u8,
+        ];
+
+        Ok(Self {
+            slave_address,
+            function_code,
+            data,
+        })
+    }
 
-        // 添加起始地址（高字节在前）
-        data.push((start_address >> 8) as u8);
-        data.push((start_address & 0xFF) as u8);
+    // 创建写多个保持寄存器请求帧 (功能码 0x10)
+    // 数据格式: [起始地址(2), 数量(2), 字节数, 值(2*N)]
+    pub fn new_write_multiple_registers(
+        slave_address: u8,
+        start_address: u16,
+        values: &[u16],
+    ) -> Result<Self, ModbusError> {
+        if values.is_empty() || values.len() > MAX_WRITE_REGISTERS as usize {
+            return Err(ModbusError::InvalidQuantity {
+                quantity: values.len(),
+                max: MAX_WRITE_REGISTERS as usize,
+            });
+        }
 
-        // 添加数量（高字节在前）
-        data.push((quantity >> 8) as u8);
-        data.push((quantity & 0xFF) as u8);
+        let function_code = RegisterType::HoldingRegister.write_multiple_code()?;
+        let quantity = values.len() as u16;
+        let mut data = Vec::with_capacity(5 + values.len() * 2);
+        data.extend_from_slice(&start_address.to_be_bytes());
+        data.extend_from_slice(&quantity.to_be_bytes());
+        data.push((values.len() * 2) as u8);
+        for value in values {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
 
         Ok(Self {
             slave_address,
@@ -95,6 +298,46 @@ impl ModbusFrame {
             data,
         })
     }
+
+    // 创建写多个线圈请求帧 (功能码 0x0F)
+    // 线圈按位打包，第一个线圈位于第一个字节的最低位
+    pub fn new_write_multiple_coils(
+        slave_address: u8,
+        start_address: u16,
+        coils: &[bool],
+    ) -> Result<Self, ModbusError> {
+        if coils.is_empty() || coils.len() > MAX_WRITE_COILS as usize {
+            return Err(ModbusError::InvalidQuantity {
+                quantity: coils.len(),
+                max: MAX_WRITE_COILS as usize,
+            });
+        }
+
+        let function_code = RegisterType::Coil.write_multiple_code()?;
+        let quantity = coils.len() as u16;
+        let packed: Vec<u8> = coils
+            .chunks(8)
+            .map(|bits| {
+                bits.iter().enumerate().fold(
+                    0u8,
+                    |byte, (i, &on)| if on { byte | (1 << i) } else { byte },
+                )
+            })
+            .collect();
+
+        let mut data = Vec::with_capacity(5 + packed.len());
+        data.extend_from_slice(&start_address.to_be_bytes());
+        data.extend_from_slice(&quantity.to_be_bytes());
+        data.push(packed.len() as u8);
+        data.extend_from_slice(&packed);
+
+        Ok(Self {
+            slave_address,
+            function_code,
+            data,
+        })
+    }
+
     // 获取数据部分
     pub fn get_data(&self) -> &[u8] {
         &self.data
@@ -107,6 +350,33 @@ impl ModbusFrame {
     pub fn get_function_code(&self) -> u8 {
         self.function_code
     }
+    // 解析读寄存器响应 (0x03/0x04) 中的寄存器值
+    // 数据格式: [字节数N, 高字节, 低字节, ...]
+    pub fn get_registers(&self) -> Result<Vec<u16>, ModbusError> {
+        let byte_count = match self.data.first() {
+            Some(&count) => count as usize,
+            None => {
+                return Err(ModbusError::InvalidLength {
+                    expected: 1,
+                    actual: 0,
+                });
+            }
+        };
+
+        let payload = &self.data[1..];
+        if payload.len() != byte_count || byte_count % 2 != 0 {
+            return Err(ModbusError::InvalidLength {
+                expected: byte_count,
+                actual: payload.len(),
+            });
+        }
+
+        Ok(payload
+            .chunks_exact(2)
+            .map(|word| u16::from_be_bytes([word[0], word[1]]))
+            .collect())
+    }
+
     // 计算帧CRC
     fn calculate_crc(&self) -> u16 {
         let crc_alg = Crc::<u16>::new(&CRC_16_MODBUS);
@@ -134,6 +404,112 @@ impl ModbusFrame {
         frame
     }
 
+    // 计算帧LRC: 地址、功能码和数据按字节求和后取二进制补码
+    fn calculate_lrc(bytes: &[u8]) -> u8 {
+        bytes
+            .iter()
+            .fold(0u8, |sum, &byte| sum.wrapping_add(byte))
+            .wrapping_neg()
+    }
+
+    // 序列化为 Modbus ASCII 帧: ':' + 十六进制(地址 功能码 数据 LRC) + CRLF
+    pub fn to_ascii_bytes(&self) -> Vec<u8> {
+        let mut binary = Vec::with_capacity(self.data.len() + 3);
+        binary.push(self.slave_address);
+        binary.push(self.function_code);
+        binary.extend_from_slice(&self.data);
+        binary.push(Self::calculate_lrc(&binary));
+
+        let mut frame = Vec::with_capacity(binary.len() * 2 + 3);
+        frame.push(b':');
+        for byte in binary {
+            frame.extend_from_slice(format!("{:02X}", byte).as_bytes());
+        }
+        frame.extend_from_slice(b"\r\n");
+
+        frame
+    }
+
+    // 从 Modbus ASCII 帧反序列化
+    pub fn from_ascii_bytes(bytes: &[u8]) -> Result<Self, ModbusError> {
+        let text = bytes
+            .strip_prefix(b":")
+            .ok_or_else(|| ModbusError::InvalidAsciiFrame("缺少起始符 ':'".to_string()))?
+            .strip_suffix(b"\r\n")
+            .ok_or_else(|| ModbusError::InvalidAsciiFrame("缺少结束符 CRLF".to_string()))?;
+
+        if text.len() % 2 != 0 {
+            return Err(ModbusError::InvalidAsciiFrame(format!(
+                "十六进制字符数为奇数: {}",
+                text.len()
+            )));
+        }
+
+        let binary = text
+            .chunks_exact(2)
+            .map(|pair| {
+                std::str::from_utf8(pair)
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| {
+                        ModbusError::InvalidAsciiFrame(format!(
+                            "无效的十六进制字符: {}",
+                            String::from_utf8_lossy(pair)
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<u8>, ModbusError>>()?;
+
+        // 地址 + 功能码 + LRC
+        if binary.len() < 3 {
+            return Err(ModbusError::InvalidLength {
+                expected: 3,
+                actual: binary.len(),
+            });
+        }
+
+        let (frame_bytes, lrc) = binary.split_at(binary.len() - 1);
+        let calculated = Self::calculate_lrc(frame_bytes);
+        if calculated != lrc[0] {
+            return Err(ModbusError::LrcMismatch {
+                calculated,
+                received: lrc[0],
+            });
+        }
+
+        Self::from_pdu(frame_bytes[0], &frame_bytes[1..])
+    }
+
+    // 根据已接收的字节推算完整 RTU 响应帧的长度
+    // 返回 None 表示字节数不足以判断，或功能码未知（只能依靠帧间静默判断结束）
+    pub fn expected_rtu_length(bytes: &[u8]) -> Option<usize> {
+        let function_code = *bytes.get(1)?;
+
+        // 异常响应: 地址 + 功能码|0x80 + 异常码 + CRC
+        if function_code & 0x80 != 0 {
+            return Some(Self::EXCEPTION_FRAME_LENGTH);
+        }
+
+        match function_code {
+            // 读响应: 地址 + 功能码 + 字节数N + N字节数据 + CRC
+            0x01..=0x04 => bytes.get(2).map(|&count| 5 + count as usize),
+            // 写响应: 原样回显地址和值/数量
+            0x05 | 0x06 | 0x0F | 0x10 => Some(Self::ECHO_FRAME_LENGTH),
+            _ => None,
+        }
+    }
+
+    // 根据已接收的字节推算完整 RTU 请求帧的长度 (从站侧使用)
+    pub fn expected_rtu_request_length(bytes: &[u8]) -> Option<usize> {
+        match *bytes.get(1)? {
+            // 读请求和写单个请求: 地址 + 功能码 + 地址(2) + 数量/值(2) + CRC
+            0x01..=0x06 => Some(Self::ECHO_FRAME_LENGTH),
+            // 写多个请求: 地址 + 功能码 + 地址(2) + 数量(2) + 字节数N + N字节数据 + CRC
+            0x0F | 0x10 => bytes.get(6).map(|&count| 9 + count as usize),
+            _ => None,
+        }
+    }
+
     // 从字节流反序列化
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, ModbusError> {
         if bytes.len() < Self::MIN_FRAME_LENGTH {
@@ -162,22 +538,549 @@ impl ModbusFrame {
             });
         }
 
+        Self::from_pdu(frame_bytes[0], &frame_bytes[1..])
+    }
+
+    // 序列化为协议数据单元 (PDU): 功能码 + 数据，不含地址和校验
+    // Modbus TCP 等不使用 RTU 帧格式的传输方式使用
+    pub fn to_pdu(&self) -> Vec<u8> {
+        let mut pdu = Vec::with_capacity(self.data.len() + 1);
+        pdu.push(self.function_code);
+        pdu.extend_from_slice(&self.data);
+        pdu
+    }
+
+    // 从从站地址和协议数据单元 (PDU) 反序列化
+    pub fn from_pdu(slave_address: u8, pdu: &[u8]) -> Result<Self, ModbusError> {
+        let Some((&function_code, data)) = pdu.split_first() else {
+            return Err(ModbusError::InvalidLength {
+                expected: 1,
+                actual: 0,
+            });
+        };
+
         // 检查异常响应
-        if frame_bytes[1] & 0x80 != 0 {
+        if function_code & 0x80 != 0 {
             return Err(ModbusError::ExceptionResponse {
-                code: frame_bytes[1] & 0x7F,
-                error: if frame_bytes.len() > 2 {
-                    frame_bytes[2]
-                } else {
-                    0
-                },
+                code: function_code & 0x7F,
+                exception: ModbusException::from(data.first().copied().unwrap_or(0)),
             });
         }
 
         Ok(Self {
-            slave_address: frame_bytes[0],
-            function_code: frame_bytes[1],
-            data: frame_bytes[2..].to_vec(),
+            slave_address,
+            function_code,
+            data: data.to_vec(),
         })
     }
 }
+
+// Modbus 请求/响应配对
+// 保存原始请求，用于校验收到的响应确实是对该请求的应答，
+// 避免共享 RS-485 总线上的过期或其他从站的响应被误认为成功
+#[derive(Debug)]
+pub struct ModbusTransaction {
+    request: ModbusFrame,
+}
+
+impl ModbusTransaction {
+    pub fn new(request: ModbusFrame) -> Self {
+        Self { request }
+    }
+
+    // 获取原始请求帧
+    pub fn request(&self) -> &ModbusFrame {
+        &self.request
+    }
+
+    // 解析响应字节流并校验是否与请求匹配
+    pub fn parse_response(&self, bytes: &[u8]) -> Result<ModbusFrame, ModbusError> {
+        let slave_address = bytes.first().copied().unwrap_or(0);
+        self.check_response(slave_address, ModbusFrame::from_bytes(bytes))
+    }
+
+    // 校验已解码的响应，slave_address 为响应中携带的从站地址
+    pub fn check_response(
+        &self,
+        slave_address: u8,
+        parsed: Result<ModbusFrame, ModbusError>,
+    ) -> Result<ModbusFrame, ModbusError> {
+        match parsed {
+            Ok(response) => {
+                self.validate(&response)?;
+                Ok(response)
+            }
+            Err(ModbusError::ExceptionResponse { code, exception }) => {
+                // 异常响应也必须来自同一从站、针对同一功能码
+                self.check_address(slave_address)?;
+                self.check_function_code(code)?;
+                Err(ModbusError::ExceptionResponse { code, exception })
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    // 校验响应帧: 从站地址、功能码、字节数以及写入回显
+    pub fn validate(&self, response: &ModbusFrame) -> Result<(), ModbusError> {
+        self.check_address(response.slave_address)?;
+        self.check_function_code(response.function_code)?;
+
+        let request_data = &self.request.data;
+        // 读取和写入请求都以 起始地址 + 数量/值 (4 字节) 开头
+        if matches!(self.request.function_code, 0x01..=0x06 | 0x0F | 0x10) && request_data.len() < 4
+        {
+            return Err(ModbusError::InvalidLength {
+                expected: 4,
+                actual: request_data.len(),
+            });
+        }
+
+        match self.request.function_code {
+            0x01..=0x04 => {
+                let quantity = u16::from_be_bytes([request_data[2], request_data[3]]) as usize;
+                let expected = if self.request.function_code <= 0x02 {
+                    quantity.div_ceil(8) // 线圈/离散输入按位打包
+                } else {
+                    quantity * 2
+                };
+
+                let byte_count = response.data.first().copied().unwrap_or(0) as usize;
+                let actual = response.data.len().saturating_sub(1);
+                if byte_count != expected || actual != expected {
+                    return Err(ModbusError::ByteCountMismatch {
+                        expected,
+                        actual: byte_count.max(actual),
+                    });
+                }
+            }
+            // 写单个: 回显地址和值
+            0x05 | 0x06 => self.check_echo(request_data, &response.data)?,
+            // 写多个: 回显起始地址和数量
+            0x0F | 0x10 => self.check_echo(&request_data[..4], &response.data)?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn check_address(&self, actual: u8) -> Result<(), ModbusError> {
+        if actual != self.request.slave_address {
+            return Err(ModbusError::AddressMismatch {
+                expected: self.request.slave_address,
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    fn check_function_code(&self, actual: u8) -> Result<(), ModbusError> {
+        if actual != self.request.function_code {
+            return Err(ModbusError::FunctionCodeMismatch {
+                expected: self.request.function_code,
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    fn check_echo(&self, expected: &[u8], actual: &[u8]) -> Result<(), ModbusError> {
+        if expected != actual {
+            return Err(ModbusError::EchoMismatch {
+                request: expected.to_vec(),
+                response: actual.to_vec(),
+            });
+        }
+        Ok(())
+    }
+}
+
+// 将寄存器地址分组为连续的块，每块最多 max_count 个寄存器
+// 返回 (起始地址, 数量) 列表，地址会先排序去重
+pub fn group_register_blocks(addresses: &[u16], max_count: u16) -> Vec<(u16, u16)> {
+    let mut sorted = addresses.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    let mut blocks: Vec<(u16, u16)> = Vec::new();
+    for address in sorted {
+        match blocks.last_mut() {
+            Some((start, count))
+                if *count < max_count && start.checked_add(*count) == Some(address) =>
+            {
+                *count += 1;
+            }
+            _ => blocks.push((address, 1)),
+        }
+    }
+
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expected_rtu_length() {
+        // 字节数不足时无法判断
+        assert_eq!(ModbusFrame::expected_rtu_length(&[]), None);
+        assert_eq!(ModbusFrame::expected_rtu_length(&[0x01]), None);
+        assert_eq!(ModbusFrame::expected_rtu_length(&[0x01, 0x03]), None);
+
+        // 读保持/输入寄存器: 5 + N
+        assert_eq!(
+            ModbusFrame::expected_rtu_length(&[0x01, 0x03, 0x02]),
+            Some(7)
+        );
+        assert_eq!(
+            ModbusFrame::expected_rtu_length(&[0x01, 0x04, 0x0A]),
+            Some(15)
+        );
+
+        // 写单个寄存器 / 写多个寄存器回显
+        assert_eq!(ModbusFrame::expected_rtu_length(&[0x01, 0x06]), Some(8));
+        assert_eq!(ModbusFrame::expected_rtu_length(&[0x01, 0x10]), Some(8));
+
+        // 异常响应
+        assert_eq!(ModbusFrame::expected_rtu_length(&[0x01, 0x83]), Some(5));
+
+        // 未知功能码
+        assert_eq!(ModbusFrame::expected_rtu_length(&[0x01, 0x2B, 0x0E]), None);
+    }
+
+    #[test]
+    fn test_read_request_round_trip() {
+        let frame =
+            ModbusFrame::new_read_request(0x01, RegisterType::HoldingRegister, 0x4000, 1).unwrap();
+        let bytes = frame.to_bytes();
+        assert_eq!(bytes, vec![0x01, 0x03, 0x40, 0x00, 0x00, 0x01, 0x91, 0xCA]);
+
+        let parsed = ModbusFrame::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.get_slave_address(), 0x01);
+        assert_eq!(parsed.get_function_code(), 0x03);
+        assert_eq!(parsed.get_data(), &[0x40, 0x00, 0x00, 0x01]);
+    }
+
+    #[test]
+    fn test_get_registers() {
+        let frame = ModbusFrame::new(0x01, 0x03, vec![0x04, 0x00, 0x1C, 0x12, 0x34]);
+        assert_eq!(frame.get_registers().unwrap(), vec![0x001C, 0x1234]);
+
+        // 字节数与数据长度不一致
+        let frame = ModbusFrame::new(0x01, 0x03, vec![0x04, 0x00, 0x1C]);
+        assert!(matches!(
+            frame.get_registers(),
+            Err(ModbusError::InvalidLength {
+                expected: 4,
+                actual: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_write_single_builders() {
+        let frame = ModbusFrame::new_write_single_register(0x01, 0x4002, 0x0001);
+        assert_eq!(frame.get_function_code(), 0x06);
+        assert_eq!(frame.get_data(), &[0x40, 0x02, 0x00, 0x01]);
+
+        let frame = ModbusFrame::new_write_single_coil(0x01, 0x0013, true);
+        assert_eq!(frame.get_function_code(), 0x05);
+        assert_eq!(frame.get_data(), &[0x00, 0x13, 0xFF, 0x00]);
+    }
+
+    #[test]
+    fn test_write_multiple_registers() {
+        let frame =
+            ModbusFrame::new_write_multiple_registers(0x01, 0x1000, &[0x000A, 0x0102]).unwrap();
+        assert_eq!(frame.get_function_code(), 0x10);
+        assert_eq!(
+            frame.get_data(),
+            &[0x10, 0x00, 0x00, 0x02, 0x04, 0x00, 0x0A, 0x01, 0x02]
+        );
+
+        // 数量超出范围
+        assert!(matches!(
+            ModbusFrame::new_write_multiple_registers(0x01, 0x0000, &[]),
+            Err(ModbusError::InvalidQuantity { quantity: 0, .. })
+        ));
+        assert!(matches!(
+            ModbusFrame::new_write_multiple_registers(0x01, 0x0000, &[0; 124]),
+            Err(ModbusError::InvalidQuantity { quantity: 124, .. })
+        ));
+    }
+
+    #[test]
+    fn test_write_multiple_coils() {
+        // 协议文档示例: 从 0x0013 写入 10 个线圈 1100 1101 01
+        let coils = [
+            true, false, true, true, false, false, true, true, true, false,
+        ];
+        let frame = ModbusFrame::new_write_multiple_coils(0x11, 0x0013, &coils).unwrap();
+        assert_eq!(frame.get_function_code(), 0x0F);
+        assert_eq!(
+            frame.get_data(),
+            &[0x00, 0x13, 0x00, 0x0A, 0x02, 0xCD, 0x01]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_write() {
+        assert!(matches!(
+            RegisterType::InputRegister.write_code(),
+            Err(ModbusError::UnsupportedWrite(RegisterType::InputRegister))
+        ));
+        assert!(matches!(
+            RegisterType::DiscreteInput.write_multiple_code(),
+            Err(ModbusError::UnsupportedWrite(RegisterType::DiscreteInput))
+        ));
+    }
+
+    #[test]
+    fn test_transaction_validates_read_response() {
+        let request =
+            ModbusFrame::new_read_request(0x01, RegisterType::HoldingRegister, 0x4001, 2).unwrap();
+        let transaction = ModbusTransaction::new(request);
+
+        let ok = ModbusFrame::new(0x01, 0x03, vec![0x04, 0x00, 0x01, 0x00, 0x00]);
+        assert!(transaction.validate(&ok).is_ok());
+
+        // 其他从站的响应
+        let foreign = ModbusFrame::new(0x02, 0x03, vec![0x04, 0x00, 0x01, 0x00, 0x00]);
+        assert!(matches!(
+            transaction.validate(&foreign),
+            Err(ModbusError::AddressMismatch {
+                expected: 0x01,
+                actual: 0x02
+            })
+        ));
+
+        // 功能码不一致
+        let wrong_code = ModbusFrame::new(0x01, 0x04, vec![0x04, 0x00, 0x01, 0x00, 0x00]);
+        assert!(matches!(
+            transaction.validate(&wrong_code),
+            Err(ModbusError::FunctionCodeMismatch {
+                expected: 0x03,
+                actual: 0x04
+            })
+        ));
+
+        // 字节数与请求数量不一致 (过期的单寄存器响应)
+        let stale = ModbusFrame::new(0x01, 0x03, vec![0x02, 0x00, 0x1C]);
+        assert!(matches!(
+            transaction.validate(&stale),
+            Err(ModbusError::ByteCountMismatch {
+                expected: 4,
+                actual: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_transaction_validates_write_echo() {
+        let request = ModbusFrame::new_write_single_register(0x01, 0x4002, 0x0001);
+        let transaction = ModbusTransaction::new(request);
+
+        let echo = ModbusFrame::new(0x01, 0x06, vec![0x40, 0x02, 0x00, 0x01]);
+        assert!(transaction.validate(&echo).is_ok());
+
+        let wrong_value = ModbusFrame::new(0x01, 0x06, vec![0x40, 0x02, 0x00, 0x00]);
+        assert!(matches!(
+            transaction.validate(&wrong_value),
+            Err(ModbusError::EchoMismatch { .. })
+        ));
+
+        let request =
+            ModbusFrame::new_write_multiple_registers(0x01, 0x1000, &[0x0001, 0x0002]).unwrap();
+        let transaction = ModbusTransaction::new(request);
+
+        let echo = ModbusFrame::new(0x01, 0x10, vec![0x10, 0x00, 0x00, 0x02]);
+        assert!(transaction.validate(&echo).is_ok());
+
+        let wrong_quantity = ModbusFrame::new(0x01, 0x10, vec![0x10, 0x00, 0x00, 0x01]);
+        assert!(matches!(
+            transaction.validate(&wrong_quantity),
+            Err(ModbusError::EchoMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_transaction_rejects_short_request() {
+        // 请求数据不足 4 字节时返回错误而不是越界
+        let transaction = ModbusTransaction::new(ModbusFrame::new(0x01, 0x03, vec![0x40]));
+        let response = ModbusFrame::new(0x01, 0x03, vec![0x02, 0x00, 0x1C]);
+        assert!(matches!(
+            transaction.validate(&response),
+            Err(ModbusError::InvalidLength {
+                expected: 4,
+                actual: 1
+            })
+        ));
+
+        let transaction = ModbusTransaction::new(ModbusFrame::new(0x01, 0x10, vec![0x10, 0x00]));
+        let response = ModbusFrame::new(0x01, 0x10, vec![0x10, 0x00]);
+        assert!(matches!(
+            transaction.validate(&response),
+            Err(ModbusError::InvalidLength {
+                expected: 4,
+                actual: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_transaction_rejects_foreign_exception() {
+        let request =
+            ModbusFrame::new_read_request(0x01, RegisterType::HoldingRegister, 0x4000, 1).unwrap();
+        let transaction = ModbusTransaction::new(request);
+
+        // 本从站的异常响应照常返回
+        let own = ModbusFrame::new(0x01, 0x83, vec![0x02]).to_bytes();
+        assert!(matches!(
+            transaction.parse_response(&own),
+            Err(ModbusError::ExceptionResponse {
+                code: 0x03,
+                exception: ModbusException::IllegalDataAddress
+            })
+        ));
+
+        // 其他从站的异常响应
+        let foreign = ModbusFrame::new(0x05, 0x83, vec![0x02]).to_bytes();
+        assert!(matches!(
+            transaction.parse_response(&foreign),
+            Err(ModbusError::AddressMismatch {
+                expected: 0x01,
+                actual: 0x05
+            })
+        ));
+    }
+
+    #[test]
+    fn test_pdu_round_trip() {
+        let frame = ModbusFrame::new_write_single_register(0x01, 0x4002, 0x0001);
+        assert_eq!(frame.to_pdu(), vec![0x06, 0x40, 0x02, 0x00, 0x01]);
+
+        let parsed = ModbusFrame::from_pdu(0x01, &frame.to_pdu()).unwrap();
+        assert_eq!(parsed.to_bytes(), frame.to_bytes());
+
+        assert!(matches!(
+            ModbusFrame::from_pdu(0x01, &[0x83, 0x02]),
+            Err(ModbusError::ExceptionResponse {
+                code: 0x03,
+                exception: ModbusException::IllegalDataAddress
+            })
+        ));
+        assert!(ModbusFrame::from_pdu(0x01, &[]).is_err());
+    }
+
+    #[test]
+    fn test_expected_rtu_request_length() {
+        let read = ModbusFrame::new_read_request(0x01, RegisterType::HoldingRegister, 0x4000, 3)
+            .unwrap()
+            .to_bytes();
+        assert_eq!(
+            ModbusFrame::expected_rtu_request_length(&read[..2]),
+            Some(8)
+        );
+
+        let write = ModbusFrame::new_write_multiple_registers(0x01, 0x4001, &[1, 0, 1])
+            .unwrap()
+            .to_bytes();
+        assert_eq!(ModbusFrame::expected_rtu_request_length(&write[..6]), None);
+        assert_eq!(
+            ModbusFrame::expected_rtu_request_length(&write[..7]),
+            Some(write.len())
+        );
+        assert_eq!(
+            ModbusFrame::expected_rtu_request_length(&[0x01, 0x2B]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ascii_round_trip() {
+        // 读 0x4000 开始的 1 个寄存器: 01+03+40+00+00+01 = 0x45，LRC = 0xBB
+        let frame =
+            ModbusFrame::new_read_request(0x01, RegisterType::HoldingRegister, 0x4000, 1).unwrap();
+        let ascii = frame.to_ascii_bytes();
+        assert_eq!(ascii, b":010340000001BB\r\n".to_vec());
+
+        let parsed = ModbusFrame::from_ascii_bytes(&ascii).unwrap();
+        assert_eq!(parsed.to_bytes(), frame.to_bytes());
+
+        // 小写十六进制同样可以解析
+        let parsed = ModbusFrame::from_ascii_bytes(b":010302001cde\r\n").unwrap();
+        assert_eq!(parsed.get_registers().unwrap(), vec![0x001C]);
+    }
+
+    #[test]
+    fn test_ascii_errors() {
+        assert!(matches!(
+            ModbusFrame::from_ascii_bytes(b":010340000001BC\r\n"),
+            Err(ModbusError::LrcMismatch {
+                calculated: 0xBB,
+                received: 0xBC
+            })
+        ));
+        assert!(matches!(
+            ModbusFrame::from_ascii_bytes(b"010340000001BB\r\n"),
+            Err(ModbusError::InvalidAsciiFrame(_))
+        ));
+        assert!(matches!(
+            ModbusFrame::from_ascii_bytes(b":010340000001BB"),
+            Err(ModbusError::InvalidAsciiFrame(_))
+        ));
+        assert!(matches!(
+            ModbusFrame::from_ascii_bytes(b":0103ZZ\r\n"),
+            Err(ModbusError::InvalidAsciiFrame(_))
+        ));
+
+        // 异常响应: 01 83 02 LRC=7A
+        assert!(matches!(
+            ModbusFrame::from_ascii_bytes(b":0183027A\r\n"),
+            Err(ModbusError::ExceptionResponse {
+                code: 0x03,
+                exception: ModbusException::IllegalDataAddress
+            })
+        ));
+    }
+
+    #[test]
+    fn test_exception_codes() {
+        for code in [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x0A, 0x0B] {
+            let exception = ModbusException::from(code);
+            assert!(!matches!(exception, ModbusException::Unknown(_)));
+            assert_eq!(exception.code(), code);
+        }
+        assert_eq!(ModbusException::from(0x08), ModbusException::Unknown(0x08));
+        assert_eq!(
+            ModbusException::from(0x0B),
+            ModbusException::GatewayTargetFailed
+        );
+
+        // 异常响应解析为类型化的异常
+        let bytes = ModbusFrame::new(0x01, 0x86, vec![0x06]).to_bytes();
+        let error = ModbusFrame::from_bytes(&bytes).unwrap_err();
+        assert_eq!(error.exception(), Some(ModbusException::Busy));
+        assert_eq!(
+            error.to_string(),
+            "Modbus异常响应: 功能码 0x06, 从站设备忙: 请稍后重试 (异常码 0x06)"
+        );
+    }
+
+    #[test]
+    fn test_group_register_blocks() {
+        let blocks = group_register_blocks(&[0x1001, 0x0000, 0x0001, 0x1000, 0x0003, 0x0001], 125);
+        assert_eq!(blocks, vec![(0x0000, 2), (0x0003, 1), (0x1000, 2)]);
+
+        // 超过单块上限时拆分
+        let addresses: Vec<u16> = (0..300).collect();
+        let blocks = group_register_blocks(&addresses, MAX_READ_REGISTERS);
+        assert_eq!(blocks, vec![(0, 125), (125, 125), (250, 50)]);
+
+        // 地址上限不会溢出
+        assert_eq!(
+            group_register_blocks(&[0xFFFE, 0xFFFF], 125),
+            vec![(0xFFFE, 2)]
+        );
+    }
+}
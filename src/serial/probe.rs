@@ -0,0 +1,186 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio_serial::Parity;
+use tokio_util::sync::CancellationToken;
+
+use crate::chip_detection::read_chip_id;
+use crate::device_io::DEVICE_SLAVE_ADDRESS;
+use crate::serial::base::SerialPortManager;
+use crate::serial::modbus::Framing;
+use crate::serial::settings::SerialSettings;
+use crate::serial::simulator::CHIP1_ID_ADDRESS;
+use crate::serial::transport::ModbusTransport;
+
+// 探测时依次尝试的波特率，按常见程度排列
+pub const PROBE_BAUD_RATES: [u32; 7] = [115200, 9600, 19200, 38400, 57600, 4800, 2400];
+// 探测时依次尝试的校验方式
+pub const PROBE_PARITIES: [Parity; 3] = [Parity::None, Parity::Even, Parity::Odd];
+// 每种组合等待应答的时间，毫秒
+pub const PROBE_TIMEOUT_MS: u64 = 150;
+
+// 生成探测顺序: 先试当前参数，再按波特率和校验方式组合，数据位、停止位等沿用当前参数
+pub fn probe_candidates(base: SerialSettings) -> Vec<SerialSettings> {
+    let mut candidates = vec![base];
+    for baud_rate in PROBE_BAUD_RATES {
+        for parity in PROBE_PARITIES {
+            let settings = SerialSettings {
+                baud_rate,
+                parity,
+                ..base
+            };
+            if !candidates.contains(&settings) {
+                candidates.push(settings);
+            }
+        }
+    }
+    candidates
+}
+
+// 发送芯片检测用的 0x4000 读请求，收到 CRC 正确的应答即视为参数匹配
+// 从站返回的异常响应同样说明线路参数正确
+async fn probe_once(transport: &dyn ModbusTransport) -> bool {
+    match read_chip_id(
+        transport,
+        DEVICE_SLAVE_ADDRESS,
+        CHIP1_ID_ADDRESS,
+        PROBE_TIMEOUT_MS,
+    )
+    .await
+    {
+        Ok(_) => true,
+        Err(e) => {
+            log::debug!("探测无应答: {}", e);
+            false
+        }
+    }
+}
+
+// 依次以候选参数打开连接并探测，返回第一个得到有效应答的参数
+// open 负责按参数打开连接；打开失败说明端口本身不可用，直接返回错误
+pub async fn probe_settings<F, Fut>(
+    candidates: &[SerialSettings],
+    mut open: F,
+) -> anyhow::Result<SerialSettings>
+where
+    F: FnMut(SerialSettings) -> Fut,
+    Fut: Future<Output = anyhow::Result<Arc<dyn ModbusTransport>>>,
+{
+    for &settings in candidates {
+        log::info!("探测串口参数: {}", settings);
+        let transport = open(settings).await?;
+        let replied = probe_once(transport.as_ref()).await;
+        transport.close().await;
+
+        if replied {
+            log::info!("探测成功: {}", settings);
+            return Ok(settings);
+        }
+    }
+
+    Err(anyhow::anyhow!(
+        "未找到能应答的串口参数 (已尝试 {} 种组合)",
+        candidates.len()
+    ))
+}
+
+// 探测串口的波特率和校验方式，base 为界面上当前的参数
+pub async fn probe_serial_port(
+    port_path: &str,
+    base: SerialSettings,
+    framing: Framing,
+    cancel_token: &CancellationToken,
+) -> anyhow::Result<SerialSettings> {
+    probe_settings(&probe_candidates(base), |settings| {
+        let manager = SerialPortManager::new_for_scpi(
+            port_path,
+            settings,
+            framing,
+            cancel_token.child_token(),
+        );
+        async move {
+            manager.open().await?;
+            Ok(manager as Arc<dyn ModbusTransport>)
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::mock::MockTransport;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_probe_candidates() {
+        let base = SerialSettings {
+            baud_rate: 9600,
+            ..SerialSettings::default()
+        };
+        let candidates = probe_candidates(base);
+
+        // 当前参数排在最前，且不重复
+        assert_eq!(candidates[0], base);
+        assert_eq!(
+            candidates.len(),
+            PROBE_BAUD_RATES.len() * PROBE_PARITIES.len()
+        );
+        assert_eq!(
+            candidates
+                .iter()
+                .filter(|settings| **settings == base)
+                .count(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_probe_finds_matching_settings() {
+        let tried = Mutex::new(Vec::new());
+
+        // 只有 19200 8E1 时从站才应答，其他组合相当于无应答
+        let found = probe_settings(&probe_candidates(SerialSettings::default()), |settings| {
+            tried.lock().unwrap().push(settings);
+            let matched = settings.baud_rate == 19200 && settings.parity == Parity::Even;
+            let slave = if matched { DEVICE_SLAVE_ADDRESS } else { 0x7F };
+            async move {
+                let mock =
+                    Arc::new(MockTransport::new("mock", slave).with_registers([(0x4000, 0x1C)]));
+                mock.open().await?;
+                Ok(mock as Arc<dyn ModbusTransport>)
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(found.to_string(), "19200 8E1");
+        // 找到后不再继续尝试
+        assert_eq!(tried.lock().unwrap().last(), Some(&found));
+    }
+
+    #[tokio::test]
+    async fn test_probe_accepts_exception_reply() {
+        // 0x4000 未定义，从站返回异常响应，参数同样有效
+        let found = probe_settings(&[SerialSettings::default()], |_| async {
+            let mock = Arc::new(MockTransport::new("mock", DEVICE_SLAVE_ADDRESS));
+            mock.open().await?;
+            Ok(mock as Arc<dyn ModbusTransport>)
+        })
+        .await
+        .unwrap();
+        assert_eq!(found, SerialSettings::default());
+    }
+
+    #[tokio::test]
+    async fn test_probe_no_reply() {
+        let error = probe_settings(&probe_candidates(SerialSettings::default()), |_| async {
+            let mock = Arc::new(MockTransport::new("mock", 0x7F));
+            mock.open().await?;
+            Ok(mock as Arc<dyn ModbusTransport>)
+        })
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("未找到"));
+    }
+}
@@ -0,0 +1,224 @@
+use std::future::Future;
+
+use tokio::time::{self, Duration};
+
+use crate::serial::modbus::ModbusError;
+
+// Modbus 事务失败的类别，用于判断是否重试
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    // 响应超时
+    Timeout,
+    // CRC / LRC 校验失败
+    Checksum,
+    // 从站返回的异常响应
+    Exception,
+    // 其他错误，例如串口未打开、帧格式错误
+    Other,
+}
+
+impl ErrorKind {
+    pub fn of(error: &anyhow::Error) -> Self {
+        match error.downcast_ref::<ModbusError>() {
+            Some(ModbusError::Timeout) => Self::Timeout,
+            Some(ModbusError::CrcMismatch { .. } | ModbusError::LrcMismatch { .. }) => {
+                Self::Checksum
+            }
+            Some(ModbusError::ExceptionResponse { .. }) => Self::Exception,
+            _ => Self::Other,
+        }
+    }
+}
+
+// 事务重试策略: 最多尝试 max_attempts 次，每次重试前等待的时间按 backoff_multiplier 倍增长
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    // 总尝试次数 (含第一次)，1 表示不重试
+    pub max_attempts: u32,
+    // 第一次重试前的等待时间
+    pub initial_backoff: Duration,
+    pub backoff_multiplier: u32,
+    pub max_backoff: Duration,
+    // 可以重试的错误类别
+    pub retryable: Vec<ErrorKind>,
+}
+
+impl Default for RetryPolicy {
+    // 超时和校验错误重试 2 次，异常响应不重试
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(50),
+            backoff_multiplier: 2,
+            max_backoff: Duration::from_millis(500),
+            retryable: vec![ErrorKind::Timeout, ErrorKind::Checksum],
+        }
+    }
+}
+
+impl RetryPolicy {
+    // 不重试
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_retryable(&self, error: &anyhow::Error) -> bool {
+        self.retryable.contains(&ErrorKind::of(error))
+    }
+
+    // 第 retry 次重试 (从 1 开始) 前的等待时间
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = self
+            .backoff_multiplier
+            .max(1)
+            .saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+// 操作结果及其使用的重试次数
+#[derive(Debug, Clone, PartialEq)]
+pub struct Retried<T> {
+    pub value: T,
+    pub retries: u32,
+}
+
+// 按策略执行操作，可重试的错误在等待后重新执行
+// 重试后仍失败时，错误附带已重试的次数，原始错误仍可 downcast
+pub async fn with_retry<T, F, Fut>(
+    policy: &RetryPolicy,
+    mut operation: F,
+) -> anyhow::Result<Retried<T>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let mut retries = 0;
+    loop {
+        match operation().await {
+            Ok(value) => return Ok(Retried { value, retries }),
+            Err(e) if retries + 1 < policy.max_attempts && policy.is_retryable(&e) => {
+                retries += 1;
+                let backoff = policy.backoff(retries);
+                log::warn!("事务失败，{:?} 后第 {} 次重试: {}", backoff, retries, e);
+                time::sleep(backoff).await;
+            }
+            Err(e) if retries > 0 => {
+                return Err(e.context(format!("已重试 {} 次", retries)));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::modbus::ModbusException;
+    use std::cell::Cell;
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy {
+            initial_backoff: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[test]
+    fn test_error_kind_and_backoff() {
+        assert_eq!(
+            ErrorKind::of(&ModbusError::Timeout.into()),
+            ErrorKind::Timeout
+        );
+        assert_eq!(
+            ErrorKind::of(
+                &ModbusError::CrcMismatch {
+                    calculated: 1,
+                    received: 2
+                }
+                .into()
+            ),
+            ErrorKind::Checksum
+        );
+        assert_eq!(
+            ErrorKind::of(&anyhow::anyhow!("Serial port not open")),
+            ErrorKind::Other
+        );
+
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(1), Duration::from_millis(50));
+        assert_eq!(policy.backoff(2), Duration::from_millis(100));
+        assert_eq!(policy.backoff(10), Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn test_retry_until_success() {
+        let calls = Cell::new(0);
+        let result = with_retry(&fast_policy(), || {
+            calls.set(calls.get() + 1);
+            let attempt = calls.get();
+            async move {
+                if attempt < 3 {
+                    Err(ModbusError::Timeout.into())
+                } else {
+                    Ok(0x1C)
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            result,
+            Retried {
+                value: 0x1C,
+                retries: 2
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retry_exhausted() {
+        let calls = Cell::new(0);
+        let error = with_retry(&fast_policy(), || {
+            calls.set(calls.get() + 1);
+            async { Err::<(), _>(ModbusError::Timeout.into()) }
+        })
+        .await
+        .unwrap_err();
+
+        assert_eq!(calls.get(), 3);
+        assert_eq!(format!("{:#}", error), "已重试 2 次: Response timeout");
+        assert!(matches!(
+            error.downcast_ref::<ModbusError>(),
+            Some(ModbusError::Timeout)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_exception_not_retried() {
+        let calls = Cell::new(0);
+        let error = with_retry(&fast_policy(), || {
+            calls.set(calls.get() + 1);
+            async {
+                Err::<(), _>(
+                    ModbusError::ExceptionResponse {
+                        code: 0x83,
+                        exception: ModbusException::IllegalDataAddress,
+                    }
+                    .into(),
+                )
+            }
+        })
+        .await
+        .unwrap_err();
+
+        assert_eq!(calls.get(), 1);
+        assert_eq!(ErrorKind::of(&error), ErrorKind::Exception);
+    }
+}
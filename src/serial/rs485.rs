@@ -0,0 +1,324 @@
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::time::{self, Duration};
+use tokio_serial::{SerialPort, SerialStream};
+
+// RS-485 收发方向控制方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DirectionControl {
+    // 适配器自动切换方向
+    #[default]
+    None,
+    // 发送期间置位 RTS
+    Rts,
+    // 发送期间置位 DTR
+    Dtr,
+    // 由 Linux 内核驱动切换 (TIOCSRS485)
+    Kernel,
+}
+
+impl DirectionControl {
+    // 由连接面板中的文本解析，例如 "自动"、"RTS"、"DTR"、"内核"
+    pub fn parse(name: &str) -> anyhow::Result<Self> {
+        match name.trim().to_uppercase().as_str() {
+            "自动" | "无" | "NONE" => Ok(Self::None),
+            "RTS" => Ok(Self::Rts),
+            "DTR" => Ok(Self::Dtr),
+            "内核" | "KERNEL" => Ok(Self::Kernel),
+            other => Err(anyhow::anyhow!("无效的RS485方向控制方式: {}", other)),
+        }
+    }
+}
+
+// RS-485 半双工模式参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rs485Config {
+    pub direction: DirectionControl,
+    // 发送时控制线的电平，true 为高电平有效
+    pub active_high: bool,
+    // 切换到发送方向后、发送首字节前的等待时间
+    pub pre_delay: Duration,
+    // 最后一个字节发出后、切回接收方向前的等待时间
+    pub post_delay: Duration,
+    // 适配器会把发送的字节回显到接收端时，从响应开头去掉这些字节
+    pub strip_echo: bool,
+}
+
+impl Default for Rs485Config {
+    // 适配器自动切换方向，控制线高电平有效，无延时
+    fn default() -> Self {
+        Self {
+            direction: DirectionControl::None,
+            active_high: true,
+            pre_delay: Duration::ZERO,
+            post_delay: Duration::ZERO,
+            strip_echo: false,
+        }
+    }
+}
+
+impl Rs485Config {
+    // 由连接面板中的文本解析，延时单位为毫秒，例如 ("RTS", "1", "0", false)
+    pub fn parse(
+        direction: &str,
+        pre_delay_ms: &str,
+        post_delay_ms: &str,
+        strip_echo: bool,
+    ) -> anyhow::Result<Self> {
+        let parse_delay = |text: &str| {
+            text.trim()
+                .parse::<u64>()
+                .map(Duration::from_millis)
+                .map_err(|_| anyhow::anyhow!("无效的RS485延时: {}", text))
+        };
+
+        Ok(Self {
+            direction: DirectionControl::parse(direction)?,
+            pre_delay: parse_delay(pre_delay_ms)?,
+            post_delay: parse_delay(post_delay_ms)?,
+            strip_echo,
+            ..Self::default()
+        })
+    }
+
+    // 是否需要由软件切换 RTS/DTR
+    pub fn toggles_control_line(&self) -> bool {
+        matches!(
+            self.direction,
+            DirectionControl::Rts | DirectionControl::Dtr
+        )
+    }
+
+    // 控制线的电平，transmit 为 true 时为发送期间的电平
+    pub fn line_level(&self, transmit: bool) -> bool {
+        transmit == self.active_high
+    }
+}
+
+// 切换 RS-485 收发方向，transmit 为 true 时切到发送
+pub fn set_direction(
+    port: &mut SerialStream,
+    config: &Rs485Config,
+    transmit: bool,
+) -> anyhow::Result<()> {
+    let level = config.line_level(transmit);
+    match config.direction {
+        DirectionControl::Rts => port.write_request_to_send(level)?,
+        DirectionControl::Dtr => port.write_data_terminal_ready(level)?,
+        DirectionControl::None | DirectionControl::Kernel => {}
+    }
+    Ok(())
+}
+
+// 按线路参数估算发送 len 个字节所需的时间
+pub fn transmit_time(len: usize, bits_per_char: u32, baud_rate: u32) -> Duration {
+    if baud_rate == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_micros(len as u64 * bits_per_char as u64 * 1_000_000 / baud_rate as u64)
+}
+
+// 读取并丢弃本机发送内容的回显
+// 回显与发送内容一致时，返回回显之后已读到的字节；
+// 字节不一致 (适配器没有回显) 时，返回已读到的全部字节，作为响应的开头。
+// 注意写单个寄存器 (0x06) 的正常响应与请求相同，只应对确实会回显的适配器开启
+pub async fn read_echo<R>(reader: &mut R, sent: &[u8], silence: Duration) -> anyhow::Result<Vec<u8>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut buffer = vec![0u8; 256];
+    let mut received = Vec::new();
+
+    while received.len() < sent.len() {
+        let n = if received.is_empty() {
+            reader.read(&mut buffer).await
+        } else {
+            match time::timeout(silence, reader.read(&mut buffer)).await {
+                Ok(result) => result,
+                // 回显中断，剩余数据交给响应帧解析处理
+                Err(_) => return Ok(received),
+            }
+        }
+        .map_err(|e| anyhow::anyhow!("Read error: {}", e))?;
+
+        if n == 0 {
+            return Err(anyhow::anyhow!("Connection closed"));
+        }
+        received.extend_from_slice(&buffer[..n]);
+
+        let compared = received.len().min(sent.len());
+        if received[..compared] != sent[..compared] {
+            log::debug!("未收到回显，按响应处理: {:02X?}", received);
+            return Ok(received);
+        }
+    }
+
+    log::debug!("已去除回显 {} 字节", sent.len());
+    Ok(received.split_off(sent.len()))
+}
+
+// Linux 内核 RS-485 模式 (struct serial_rs485)
+#[cfg(target_os = "linux")]
+#[repr(C)]
+struct SerialRs485 {
+    flags: u32,
+    delay_rts_before_send: u32,
+    delay_rts_after_send: u32,
+    padding: [u32; 5],
+}
+
+#[cfg(target_os = "linux")]
+const SER_RS485_ENABLED: u32 = 1 << 0;
+#[cfg(target_os = "linux")]
+const SER_RS485_RTS_ON_SEND: u32 = 1 << 1;
+#[cfg(target_os = "linux")]
+const SER_RS485_RTS_AFTER_SEND: u32 = 1 << 2;
+
+// 内核 RS-485 模式的标志: 高电平有效时发送期间置位 RTS，否则发送结束后置位
+#[cfg(target_os = "linux")]
+fn kernel_flags(config: &Rs485Config) -> u32 {
+    SER_RS485_ENABLED
+        | if config.active_high {
+            SER_RS485_RTS_ON_SEND
+        } else {
+            SER_RS485_RTS_AFTER_SEND
+        }
+}
+
+// 通过 TIOCSRS485 让驱动在发送时自动切换 RTS
+#[cfg(target_os = "linux")]
+pub fn enable_kernel_rs485(port: &SerialStream, config: &Rs485Config) -> anyhow::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let rs485 = SerialRs485 {
+        flags: kernel_flags(config),
+        delay_rts_before_send: config.pre_delay.as_millis() as u32,
+        delay_rts_after_send: config.post_delay.as_millis() as u32,
+        padding: [0; 5],
+    };
+
+    // SAFETY: fd 为已打开的串口，rs485 按内核 struct serial_rs485 布局
+    let result = unsafe {
+        libc::ioctl(
+            port.as_raw_fd(),
+            libc::TIOCSRS485,
+            &rs485 as *const SerialRs485,
+        )
+    };
+    if result < 0 {
+        return Err(anyhow::anyhow!(
+            "设置内核RS485模式失败: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn enable_kernel_rs485(_port: &SerialStream, _config: &Rs485Config) -> anyhow::Result<()> {
+    Err(anyhow::anyhow!("当前平台不支持内核RS485模式"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+
+    const SILENCE: Duration = Duration::from_millis(20);
+
+    #[test]
+    fn test_direction_line_level() {
+        // 默认高电平有效: 发送时置位，接收时复位
+        let config = Rs485Config {
+            direction: DirectionControl::Rts,
+            ..Rs485Config::default()
+        };
+        assert!(config.active_high);
+        assert!(config.toggles_control_line());
+        assert!(config.line_level(true));
+        assert!(!config.line_level(false));
+
+        let inverted = Rs485Config {
+            active_high: false,
+            ..config
+        };
+        assert!(!inverted.line_level(true));
+        assert!(inverted.line_level(false));
+
+        assert!(!Rs485Config::default().toggles_control_line());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_kernel_flags() {
+        let config = Rs485Config {
+            direction: DirectionControl::Kernel,
+            ..Rs485Config::default()
+        };
+        assert_eq!(
+            kernel_flags(&config),
+            SER_RS485_ENABLED | SER_RS485_RTS_ON_SEND
+        );
+
+        let inverted = Rs485Config {
+            active_high: false,
+            ..config
+        };
+        assert_eq!(
+            kernel_flags(&inverted),
+            SER_RS485_ENABLED | SER_RS485_RTS_AFTER_SEND
+        );
+    }
+
+    #[test]
+    fn test_parse_rs485_config() {
+        let config = Rs485Config::parse("RTS", "2", "1", true).unwrap();
+        assert_eq!(config.direction, DirectionControl::Rts);
+        assert_eq!(config.pre_delay, Duration::from_millis(2));
+        assert_eq!(config.post_delay, Duration::from_millis(1));
+        assert!(config.strip_echo);
+        assert!(config.active_high);
+
+        assert_eq!(
+            Rs485Config::parse("自动", "0", "0", false).unwrap(),
+            Rs485Config::default()
+        );
+        assert_eq!(
+            DirectionControl::parse("内核").unwrap(),
+            DirectionControl::Kernel
+        );
+        assert!(Rs485Config::parse("CTS", "0", "0", false).is_err());
+        assert!(Rs485Config::parse("DTR", "-1", "0", false).is_err());
+    }
+
+    #[test]
+    fn test_transmit_time() {
+        // 9600bps 8N1: 8字节 * 10位 ≈ 8.33ms
+        assert_eq!(transmit_time(8, 10, 9600), Duration::from_micros(8333));
+        assert_eq!(transmit_time(8, 10, 0), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_read_echo_stripped() {
+        let (mut device, mut host) = tokio::io::duplex(64);
+        let sent = [0x01, 0x03, 0x40, 0x00, 0x00, 0x01, 0x91, 0xCA];
+
+        // 回显和响应开头在同一次读取中到达
+        let mut bytes = sent.to_vec();
+        bytes.extend_from_slice(&[0x01, 0x03]);
+        device.write_all(&bytes).await.unwrap();
+
+        let rest = read_echo(&mut host, &sent, SILENCE).await.unwrap();
+        assert_eq!(rest, vec![0x01, 0x03]);
+    }
+
+    #[tokio::test]
+    async fn test_read_echo_without_echo() {
+        let (mut device, mut host) = tokio::io::duplex(64);
+        let sent = [0x01, 0x03, 0x40, 0x00, 0x00, 0x01, 0x91, 0xCA];
+
+        // 没有回显时直接收到响应，已读字节原样返回
+        device.write_all(&[0x01, 0x03, 0x02, 0x00]).await.unwrap();
+        let rest = read_echo(&mut host, &sent, SILENCE).await.unwrap();
+        assert_eq!(rest, vec![0x01, 0x03, 0x02, 0x00]);
+    }
+}
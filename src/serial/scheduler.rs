@@ -0,0 +1,353 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use tokio::sync::{Notify, watch};
+
+use crate::serial::modbus::{ModbusFrame, ModbusTransaction};
+use crate::serial::transport::ModbusTransport;
+
+// 事务优先级，同一连接上排队的事务按优先级依次执行
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    // 界面上的单次读写、IO 设置和连接时的芯片检测
+    Interactive = 0,
+    // 读取 / 写入器件、总线扫描等批量操作
+    Bulk = 1,
+    // 后台的 IO 状态轮询
+    Polling = 2,
+}
+
+const PRIORITY_COUNT: usize = 3;
+
+// 各优先级排队中的事务数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueDepth {
+    pub interactive: usize,
+    pub bulk: usize,
+    pub polling: usize,
+    // 批量写入期间轮询暂停
+    pub polling_paused: bool,
+}
+
+impl QueueDepth {
+    pub fn total(&self) -> usize {
+        self.interactive + self.bulk + self.polling
+    }
+}
+
+// 界面显示，例如 "队列: 交互 0 / 批量 3 / 轮询 1 (轮询暂停)"
+impl std::fmt::Display for QueueDepth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "队列: 交互 {} / 批量 {} / 轮询 {}",
+            self.interactive, self.bulk, self.polling
+        )?;
+        if self.polling_paused {
+            write!(f, " (轮询暂停)")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    // 各优先级等待中的事务数，下标为 Priority
+    waiting: [usize; PRIORITY_COUNT],
+    // 是否有事务正在执行
+    busy: bool,
+    // 未结束的轮询暂停次数
+    polling_pauses: usize,
+}
+
+impl SchedulerState {
+    // 连接空闲、没有更高优先级的事务在等待，且轮询未暂停时才能执行
+    fn can_run(&self, priority: Priority) -> bool {
+        if self.busy {
+            return false;
+        }
+        if priority == Priority::Polling && self.polling_pauses > 0 {
+            return false;
+        }
+        self.waiting[..priority as usize]
+            .iter()
+            .all(|&count| count == 0)
+    }
+
+    fn depth(&self) -> QueueDepth {
+        QueueDepth {
+            interactive: self.waiting[Priority::Interactive as usize],
+            bulk: self.waiting[Priority::Bulk as usize],
+            polling: self.waiting[Priority::Polling as usize],
+            polling_paused: self.polling_pauses > 0,
+        }
+    }
+}
+
+// 单个连接的事务调度器
+// 同一时刻只有一个事务在执行，空闲时优先执行交互事务，其次批量事务，最后是轮询，
+// 这样界面操作不会排在轮询或批量读写之后
+pub struct TransactionScheduler {
+    inner: Arc<dyn ModbusTransport>,
+    state: Mutex<SchedulerState>,
+    // 状态变化 (事务结束、暂停解除) 时唤醒等待中的事务
+    changed: Notify,
+    depth: watch::Sender<QueueDepth>,
+}
+
+impl TransactionScheduler {
+    pub fn new(inner: Arc<dyn ModbusTransport>) -> Arc<Self> {
+        Arc::new(Self {
+            inner,
+            state: Mutex::new(SchedulerState::default()),
+            changed: Notify::new(),
+            depth: watch::channel(QueueDepth::default()).0,
+        })
+    }
+
+    // 以指定优先级访问连接的传输层，经由它发起的事务都进入本调度器排队
+    pub fn transport(self: &Arc<Self>, priority: Priority) -> Arc<dyn ModbusTransport> {
+        Arc::new(ScheduledTransport {
+            scheduler: Arc::clone(self),
+            priority,
+        })
+    }
+
+    pub fn queue_depth(&self) -> QueueDepth {
+        self.state.lock().unwrap().depth()
+    }
+
+    // 订阅排队深度的变化
+    pub fn subscribe_depth(&self) -> watch::Receiver<QueueDepth> {
+        self.depth.subscribe()
+    }
+
+    // 暂停轮询，返回的守卫释放后恢复；已开始的轮询事务会执行完
+    pub fn pause_polling(self: &Arc<Self>) -> PollingPause {
+        self.update(|state| state.polling_pauses += 1);
+        PollingPause {
+            scheduler: Arc::clone(self),
+        }
+    }
+
+    fn update(&self, f: impl FnOnce(&mut SchedulerState)) {
+        let depth = {
+            let mut state = self.state.lock().unwrap();
+            f(&mut state);
+            state.depth()
+        };
+        self.depth.send_replace(depth);
+        self.changed.notify_waiters();
+    }
+
+    // 排队等待执行权
+    async fn acquire(&self, priority: Priority) -> Turn<'_> {
+        self.update(|state| state.waiting[priority as usize] += 1);
+        // 等待中被取消 (future 被丢弃) 时撤销排队
+        let queued = Queued {
+            scheduler: self,
+            priority,
+        };
+
+        loop {
+            let notified = self.changed.notified();
+            tokio::pin!(notified);
+            // 先登记唤醒再检查状态，避免错过检查之后的通知
+            notified.as_mut().enable();
+
+            {
+                let mut state = self.state.lock().unwrap();
+                if state.can_run(priority) {
+                    state.waiting[priority as usize] -= 1;
+                    state.busy = true;
+                    let depth = state.depth();
+                    drop(state);
+                    std::mem::forget(queued);
+                    self.depth.send_replace(depth);
+                    return Turn { scheduler: self };
+                }
+            }
+
+            notified.await;
+        }
+    }
+}
+
+// 排队中的事务
+struct Queued<'a> {
+    scheduler: &'a TransactionScheduler,
+    priority: Priority,
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        let priority = self.priority as usize;
+        self.scheduler.update(|state| state.waiting[priority] -= 1);
+    }
+}
+
+// 正在执行的事务，结束时让出连接
+struct Turn<'a> {
+    scheduler: &'a TransactionScheduler,
+}
+
+impl Drop for Turn<'_> {
+    fn drop(&mut self) {
+        self.scheduler.update(|state| state.busy = false);
+    }
+}
+
+// 轮询暂停守卫
+pub struct PollingPause {
+    scheduler: Arc<TransactionScheduler>,
+}
+
+impl Drop for PollingPause {
+    fn drop(&mut self) {
+        self.scheduler.update(|state| state.polling_pauses -= 1);
+    }
+}
+
+// 带优先级的传输层，事务先在调度器中排队再交给底层连接
+struct ScheduledTransport {
+    scheduler: Arc<TransactionScheduler>,
+    priority: Priority,
+}
+
+#[async_trait]
+impl ModbusTransport for ScheduledTransport {
+    fn get_port(&self) -> &str {
+        self.scheduler.inner.get_port()
+    }
+
+    fn is_open(&self) -> bool {
+        self.scheduler.inner.is_open()
+    }
+
+    fn timeout_ms(&self) -> u64 {
+        self.scheduler.inner.timeout_ms()
+    }
+
+    async fn open(&self) -> anyhow::Result<()> {
+        self.scheduler.inner.open().await
+    }
+
+    async fn close(&self) {
+        self.scheduler.inner.close().await
+    }
+
+    // 超时从事务开始执行时计算，不包括排队时间
+    async fn transact(
+        &self,
+        transaction: ModbusTransaction,
+        timeout_ms: u64,
+    ) -> anyhow::Result<ModbusFrame> {
+        let _turn = self.scheduler.acquire(self.priority).await;
+        self.scheduler.inner.transact(transaction, timeout_ms).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::mock::MockTransport;
+    use std::time::Duration;
+
+    async fn open_scheduler() -> Arc<TransactionScheduler> {
+        let mock = Arc::new(MockTransport::new("mock", 1).with_registers([(0x4001, 1)]));
+        mock.open().await.unwrap();
+        TransactionScheduler::new(mock)
+    }
+
+    // 等待指定数量的事务进入队列
+    async fn wait_for_depth(scheduler: &TransactionScheduler, total: usize) {
+        let mut depth = scheduler.subscribe_depth();
+        depth
+            .wait_for(|depth| depth.total() == total)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_priority_order() {
+        let scheduler = open_scheduler().await;
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        // 占住连接，让后续事务排队
+        let turn = scheduler.acquire(Priority::Bulk).await;
+
+        let mut tasks = Vec::new();
+        for priority in [Priority::Polling, Priority::Bulk, Priority::Interactive] {
+            let transport = scheduler.transport(priority);
+            let order = order.clone();
+            tasks.push(tokio::spawn(async move {
+                transport.read_registers(1, 0x4001, 1).await.unwrap();
+                order.lock().unwrap().push(priority);
+            }));
+        }
+        wait_for_depth(&scheduler, 3).await;
+        assert_eq!(
+            scheduler.queue_depth(),
+            QueueDepth {
+                interactive: 1,
+                bulk: 1,
+                polling: 1,
+                polling_paused: false
+            }
+        );
+
+        drop(turn);
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec![Priority::Interactive, Priority::Bulk, Priority::Polling]
+        );
+        assert_eq!(scheduler.queue_depth().total(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_polling_paused() {
+        let scheduler = open_scheduler().await;
+        let pause = scheduler.pause_polling();
+
+        let polling = scheduler.transport(Priority::Polling);
+        let poll = tokio::spawn(async move { polling.read_registers(1, 0x4001, 1).await });
+        wait_for_depth(&scheduler, 1).await;
+        assert!(scheduler.queue_depth().polling_paused);
+
+        // 暂停期间批量事务不受排队的轮询影响
+        let bulk = scheduler.transport(Priority::Bulk);
+        tokio::time::timeout(Duration::from_secs(1), bulk.read_registers(1, 0x4001, 1))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!poll.is_finished());
+
+        drop(pause);
+        assert_eq!(poll.await.unwrap().unwrap(), vec![1]);
+        assert_eq!(scheduler.queue_depth(), QueueDepth::default());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_while_queued() {
+        let scheduler = open_scheduler().await;
+        let turn = scheduler.acquire(Priority::Interactive).await;
+
+        let polling = scheduler.transport(Priority::Polling);
+        let result = tokio::time::timeout(
+            Duration::from_millis(20),
+            polling.read_registers(1, 0x4001, 1),
+        )
+        .await;
+        assert!(result.is_err());
+        // 被取消的事务不再计入队列
+        assert_eq!(scheduler.queue_depth().total(), 0);
+
+        drop(turn);
+        let bulk = scheduler.transport(Priority::Bulk);
+        assert_eq!(bulk.read_registers(1, 0x4001, 1).await.unwrap(), vec![1]);
+    }
+}
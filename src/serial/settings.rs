@@ -0,0 +1,194 @@
+use std::fmt;
+
+use tokio::time::Duration;
+use tokio_serial::{DataBits, FlowControl, Parity, SerialPortBuilder, StopBits};
+
+use crate::serial::rs485::Rs485Config;
+
+// 串口线路参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerialSettings {
+    pub baud_rate: u32,
+    pub data_bits: DataBits,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+    pub flow_control: FlowControl,
+    // 串口读取超时时间，毫秒
+    pub timeout_ms: u64,
+    // RS-485 半双工模式
+    pub rs485: Rs485Config,
+}
+
+impl Default for SerialSettings {
+    // 115200 8N1，无流控
+    fn default() -> Self {
+        Self {
+            baud_rate: 115200,
+            data_bits: DataBits::Eight,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+            flow_control: FlowControl::None,
+            timeout_ms: 200,
+            rs485: Rs485Config::default(),
+        }
+    }
+}
+
+impl SerialSettings {
+    // 由连接面板中的文本解析，例如 ("9600", "8", "E", "1", "无", "200")，
+    // RS-485 参数由 Rs485Config::parse 解析后传入
+    pub fn parse(
+        baud_rate: &str,
+        data_bits: &str,
+        parity: &str,
+        stop_bits: &str,
+        flow_control: &str,
+        timeout_ms: &str,
+        rs485: Rs485Config,
+    ) -> anyhow::Result<Self> {
+        let baud_rate = baud_rate
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|&baud| baud > 0)
+            .ok_or_else(|| anyhow::anyhow!("无效的波特率: {}", baud_rate))?;
+
+        let data_bits = match data_bits.trim() {
+            "5" => DataBits::Five,
+            "6" => DataBits::Six,
+            "7" => DataBits::Seven,
+            "8" => DataBits::Eight,
+            other => return Err(anyhow::anyhow!("无效的数据位: {}", other)),
+        };
+
+        let parity = match parity.trim().to_uppercase().as_str() {
+            "N" | "NONE" | "无" => Parity::None,
+            "E" | "EVEN" | "偶" => Parity::Even,
+            "O" | "ODD" | "奇" => Parity::Odd,
+            other => return Err(anyhow::anyhow!("无效的校验位: {}", other)),
+        };
+
+        let stop_bits = match stop_bits.trim() {
+            "1" => StopBits::One,
+            "2" => StopBits::Two,
+            other => return Err(anyhow::anyhow!("无效的停止位: {}", other)),
+        };
+
+        let flow_control = match flow_control.trim().to_uppercase().as_str() {
+            "无" | "NONE" => FlowControl::None,
+            "软件" | "XON/XOFF" | "SOFTWARE" => FlowControl::Software,
+            "硬件" | "RTS/CTS" | "HARDWARE" => FlowControl::Hardware,
+            other => return Err(anyhow::anyhow!("无效的流控方式: {}", other)),
+        };
+
+        let timeout_ms = timeout_ms
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| anyhow::anyhow!("无效的超时时间: {}", timeout_ms))?;
+
+        Ok(Self {
+            baud_rate,
+            data_bits,
+            parity,
+            stop_bits,
+            flow_control,
+            timeout_ms,
+            rs485,
+        })
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms)
+    }
+
+    pub fn data_bits_count(&self) -> u32 {
+        match self.data_bits {
+            DataBits::Five => 5,
+            DataBits::Six => 6,
+            DataBits::Seven => 7,
+            DataBits::Eight => 8,
+        }
+    }
+
+    pub fn stop_bits_count(&self) -> u32 {
+        match self.stop_bits {
+            StopBits::One => 1,
+            StopBits::Two => 2,
+        }
+    }
+
+    // 校验方式的单字符写法: N / E / O
+    pub fn parity_char(&self) -> char {
+        match self.parity {
+            Parity::None => 'N',
+            Parity::Even => 'E',
+            Parity::Odd => 'O',
+        }
+    }
+
+    // 每个字符的位数: 起始位 + 数据位 + 校验位 + 停止位
+    pub fn bits_per_char(&self) -> u32 {
+        let parity_bits = if self.parity == Parity::None { 0 } else { 1 };
+        1 + self.data_bits_count() + parity_bits + self.stop_bits_count()
+    }
+
+    // 生成打开串口用的构造器
+    pub fn builder(&self, port_path: &str) -> SerialPortBuilder {
+        tokio_serial::new(port_path, self.baud_rate)
+            .data_bits(self.data_bits)
+            .parity(self.parity)
+            .stop_bits(self.stop_bits)
+            .flow_control(self.flow_control)
+            .timeout(self.timeout())
+    }
+}
+
+// 常用写法，例如 "9600 8E1"
+impl fmt::Display for SerialSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}{}{}",
+            self.baud_rate,
+            self.data_bits_count(),
+            self.parity_char(),
+            self.stop_bits_count()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_settings() {
+        let rs485 = Rs485Config::parse("RTS", "1", "0", false).unwrap();
+        let settings = SerialSettings::parse("9600", "8", "E", "1", "无", "500", rs485).unwrap();
+        assert_eq!(settings.baud_rate, 9600);
+        assert_eq!(settings.parity, Parity::Even);
+        assert_eq!(settings.stop_bits, StopBits::One);
+        assert_eq!(settings.flow_control, FlowControl::None);
+        assert_eq!(settings.timeout_ms, 500);
+        assert_eq!(settings.bits_per_char(), 11);
+        assert_eq!(settings.to_string(), "9600 8E1");
+        assert_eq!(settings.rs485, rs485);
+
+        let settings = SerialSettings::parse("19200", "8", "N", "2", "硬件", "200", rs485).unwrap();
+        assert_eq!(settings.to_string(), "19200 8N2");
+        assert_eq!(settings.flow_control, FlowControl::Hardware);
+
+        assert_eq!(SerialSettings::default().to_string(), "115200 8N1");
+    }
+
+    #[test]
+    fn test_parse_settings_errors() {
+        let rs485 = Rs485Config::default();
+        assert!(SerialSettings::parse("abc", "8", "N", "1", "无", "200", rs485).is_err());
+        assert!(SerialSettings::parse("0", "8", "N", "1", "无", "200", rs485).is_err());
+        assert!(SerialSettings::parse("9600", "9", "N", "1", "无", "200", rs485).is_err());
+        assert!(SerialSettings::parse("9600", "8", "X", "1", "无", "200", rs485).is_err());
+        assert!(SerialSettings::parse("9600", "8", "N", "3", "无", "200", rs485).is_err());
+        assert!(SerialSettings::parse("9600", "8", "N", "1", "无", "-1", rs485).is_err());
+    }
+}
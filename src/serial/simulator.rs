@@ -0,0 +1,256 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio_util::sync::CancellationToken;
+
+use crate::csv_handler::CsvHandler;
+use crate::device_io::{CHIP1_IO_BASE, CHIP2_IO_BASE, DEVICE_SLAVE_ADDRESS, IO_COUNT};
+use crate::serial::base::{inter_frame_silence, read_rtu_request};
+use crate::serial::mock::MockTransport;
+use crate::serial::modbus::ModbusFrame;
+
+// 模拟器连接的 URL 前缀，例如 "sim://" 或 "sim://demo.csv"
+pub const SIMULATOR_SCHEME: &str = "sim://";
+
+// 芯片ID寄存器，芯片一 (MALD) 读出 0x1C/0x1D，芯片二 (MATA) 读出 0x10/0x11
+pub const CHIP1_ID_ADDRESS: u16 = 0x4000;
+pub const CHIP2_ID_ADDRESS: u16 = 0xC000;
+pub const MALD_CHIP_ID: u16 = 0x1C;
+pub const MATA_CHIP_ID: u16 = 0x10;
+
+// 模拟带两颗芯片的板卡: 芯片一 MALD、芯片二 MATA
+// 寄存器包括芯片ID、IO1-IO3 以及从 CSV 载入的寄存器表；
+// 可以直接作为进程内传输层使用，也可以在 Linux 上通过伪终端当作串口访问
+pub struct DeviceSimulator {
+    device: Arc<MockTransport>,
+}
+
+impl DeviceSimulator {
+    pub fn new(port_name: &str, chip1_id: u16, chip2_id: u16) -> Self {
+        let device = MockTransport::new(port_name, DEVICE_SLAVE_ADDRESS).with_registers(
+            [(CHIP1_ID_ADDRESS, chip1_id), (CHIP2_ID_ADDRESS, chip2_id)]
+                .into_iter()
+                .chain((0..IO_COUNT).map(|offset| (CHIP1_IO_BASE + offset, 0)))
+                .chain((0..IO_COUNT).map(|offset| (CHIP2_IO_BASE + offset, 0))),
+        );
+
+        Self {
+            device: Arc::new(device),
+        }
+    }
+
+    // 由连接标识创建模拟器，"sim://" 之后的部分作为寄存器表 CSV 路径
+    pub fn from_url(url: &str) -> anyhow::Result<Self> {
+        let simulator = Self::new(url, MALD_CHIP_ID, MATA_CHIP_ID);
+
+        let csv_path = url.strip_prefix(SIMULATOR_SCHEME).unwrap_or_default();
+        if !csv_path.is_empty() {
+            simulator.load_csv(Path::new(csv_path))?;
+        }
+
+        Ok(simulator)
+    }
+
+    // 从 CSV 载入寄存器表，寄存器的初始值为复位值，没有复位值时为 Value 列
+    pub fn load_csv(&self, file_path: &Path) -> anyhow::Result<usize> {
+        let records = CsvHandler::parse_csv_file(file_path)?;
+        for record in &records {
+            let def = &record.def;
+            let value = match def.reset {
+                Some(reset) => reset,
+                None => def.parse_value(&record.value).unwrap_or_else(|e| {
+                    log::warn!("模拟器寄存器 {} 初始值无效，使用0: {}", record.page_addr, e);
+                    0
+                }),
+            };
+            // 32 位寄存器占两个地址
+            for (address, word) in def.addresses().into_iter().zip(def.split_words(value)) {
+                self.device.set_register(address, word);
+            }
+        }
+
+        log::info!("模拟器载入寄存器表 {:?}: {} 条", file_path, records.len());
+        Ok(records.len())
+    }
+
+    // 进程内传输层，可直接加入注册表
+    pub fn transport(&self) -> Arc<MockTransport> {
+        self.device.clone()
+    }
+
+    // 设置 IO 输入电平，index 为 0-2 对应 IO1-IO3
+    pub fn set_io(&self, io_base: u16, index: u16, level: bool) {
+        self.device.set_register(io_base + index, level as u16);
+    }
+
+    // 在字节流上以 RTU 从站身份应答请求，直到流关闭或取消
+    // CRC 错误或其他从站地址的请求不应答，与实际从站一致
+    pub async fn serve<S>(
+        &self,
+        mut stream: S,
+        cancel_token: CancellationToken,
+    ) -> anyhow::Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let silence = inter_frame_silence(0);
+
+        loop {
+            let request = tokio::select! {
+                _ = cancel_token.cancelled() => return Ok(()),
+                request = read_rtu_request(&mut stream, silence) => request?,
+            };
+
+            let frame = match ModbusFrame::from_bytes(&request) {
+                Ok(frame) => frame,
+                Err(e) => {
+                    log::warn!("模拟器丢弃无效请求 {:02X?}: {}", request, e);
+                    continue;
+                }
+            };
+            if frame.get_slave_address() != DEVICE_SLAVE_ADDRESS {
+                continue;
+            }
+
+            let pdu = self.device.handle_request(&frame);
+            let response = ModbusFrame::new(DEVICE_SLAVE_ADDRESS, pdu[0], pdu[1..].to_vec());
+            stream.write_all(&response.to_bytes()).await?;
+        }
+    }
+
+    // 创建伪终端并在后台应答，返回可供串口打开的设备路径 (例如 /dev/pts/3)
+    // 需要在 tokio 运行时上下文中调用
+    #[cfg(unix)]
+    pub fn spawn_pty(self: Arc<Self>, cancel_token: CancellationToken) -> anyhow::Result<String> {
+        use tokio_serial::{SerialPort, SerialStream};
+
+        let (master, mut slave) = SerialStream::pair()?;
+        // 允许串口管理器再次打开从端路径
+        slave.set_exclusive(false)?;
+        let path = slave
+            .name()
+            .ok_or_else(|| anyhow::anyhow!("无法获取伪终端路径"))?;
+
+        tokio::spawn(async move {
+            // 持有从端，避免没有打开者时主端读取出错
+            let _slave = slave;
+            if let Err(e) = self.serve(master, cancel_token).await {
+                log::error!("模拟器退出: {}", e);
+            }
+        });
+
+        log::info!("模拟器伪终端: {}", path);
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chip_detection::{ChipType, detect_all_chips};
+    use crate::serial::base::read_rtu_frame;
+    use crate::serial::modbus::{ModbusTransaction, RegisterType};
+    use crate::serial::transport::ModbusTransport;
+
+    #[tokio::test]
+    async fn test_simulator_in_process() {
+        let simulator = DeviceSimulator::from_url("sim://demo.csv").unwrap();
+        let transport = simulator.transport();
+        transport.open().await.unwrap();
+
+        let (chip1, chip2) = detect_all_chips(transport.clone()).await;
+        assert_eq!(
+            (chip1.chip_type, chip2.chip_type),
+            (ChipType::family("MALD"), ChipType::family("MATA"))
+        );
+
+        // demo.csv 中的寄存器可以读写
+        assert_eq!(
+            transport.read_registers(0x01, 0x0000, 2).await.unwrap(),
+            vec![0x72, 0x05]
+        );
+        transport.write_register(0x01, 0x1001, 0x33).await.unwrap();
+        assert_eq!(transport.register(0x1001), Some(0x33));
+
+        simulator.set_io(CHIP2_IO_BASE, 1, true);
+        assert_eq!(
+            transport
+                .read_registers(0x01, CHIP2_IO_BASE, 3)
+                .await
+                .unwrap(),
+            vec![0, 1, 0]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_simulator_serves_rtu_stream() {
+        let simulator = Arc::new(DeviceSimulator::new("sim://", 0x1D, 0x11));
+        let (device, mut host) = tokio::io::duplex(256);
+        let cancel_token = CancellationToken::new();
+
+        let server = {
+            let simulator = simulator.clone();
+            let cancel_token = cancel_token.clone();
+            tokio::spawn(async move { simulator.serve(device, cancel_token).await })
+        };
+
+        // 其他从站地址的请求不应答，之后的请求照常应答
+        let other =
+            ModbusFrame::new_read_request(0x02, RegisterType::HoldingRegister, 0x4000, 1).unwrap();
+        host.write_all(&other.to_bytes()).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let request =
+            ModbusFrame::new_read_request(0x01, RegisterType::HoldingRegister, 0xC000, 4).unwrap();
+        let transaction = ModbusTransaction::new(request.clone());
+        host.write_all(&request.to_bytes()).await.unwrap();
+        let response = read_rtu_frame(&mut host, inter_frame_silence(0))
+            .await
+            .unwrap();
+        let frame = transaction.parse_response(&response).unwrap();
+        assert_eq!(frame.get_registers().unwrap(), vec![0x11, 0, 0, 0]);
+
+        // 写多个寄存器
+        let request = ModbusFrame::new_write_multiple_registers(0x01, 0x4001, &[1, 1, 0]).unwrap();
+        let transaction = ModbusTransaction::new(request.clone());
+        host.write_all(&request.to_bytes()).await.unwrap();
+        let response = read_rtu_frame(&mut host, inter_frame_silence(0))
+            .await
+            .unwrap();
+        transaction.parse_response(&response).unwrap();
+        assert_eq!(simulator.transport().register(0x4002), Some(1));
+
+        cancel_token.cancel();
+        server.await.unwrap().unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_simulator_over_pty() {
+        use crate::serial::base::SerialPortManager;
+        use crate::serial::modbus::Framing;
+        use crate::serial::settings::SerialSettings;
+
+        let simulator = Arc::new(DeviceSimulator::new("sim://", MALD_CHIP_ID, MATA_CHIP_ID));
+        let cancel_token = CancellationToken::new();
+        let path = simulator.spawn_pty(cancel_token.clone()).unwrap();
+
+        // 与真实串口相同的打开和检测流程
+        let manager = SerialPortManager::new_for_scpi(
+            &path,
+            SerialSettings::default(),
+            Framing::Rtu,
+            cancel_token.child_token(),
+        );
+        manager.open().await.unwrap();
+        let (chip1, chip2) = detect_all_chips(manager.clone()).await;
+        assert_eq!(
+            (chip1.chip_type, chip2.chip_type),
+            (ChipType::family("MALD"), ChipType::family("MATA"))
+        );
+
+        manager.close().await;
+        cancel_token.cancel();
+    }
+}
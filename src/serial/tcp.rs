@@ -0,0 +1,546 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU16, Ordering};
+
+use async_trait::async_trait;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tokio::time::{self, Duration};
+
+use crate::serial::base::read_rtu_frame;
+use crate::serial::modbus::{ModbusError, ModbusFrame, ModbusTransaction};
+use crate::serial::transport::{ModbusTransport, TCP_RTU_SCHEME};
+
+// MBAP 报文头长度: 事务ID(2) + 协议ID(2) + 长度(2) + 单元ID(1)
+const MBAP_HEADER_LENGTH: usize = 7;
+// Modbus TCP 的 PDU 最大长度
+const MAX_PDU_LENGTH: usize = 253;
+
+// RTU over TCP 的帧间静默时间
+// 串口服务器转发时会引入网络抖动，不能使用串口的 3.5 字符时间
+const NETWORK_FRAME_SILENCE: Duration = Duration::from_millis(50);
+
+// 建立 TCP 连接，超时返回错误
+async fn connect(address: &str, timeout: Duration) -> anyhow::Result<TcpStream> {
+    match time::timeout(timeout, TcpStream::connect(address)).await {
+        Ok(Ok(stream)) => {
+            // Modbus 请求较小，关闭 Nagle 算法以降低延迟
+            stream.set_nodelay(true)?;
+            Ok(stream)
+        }
+        Ok(Err(e)) => Err(anyhow::anyhow!(e)),
+        Err(_) => Err(anyhow::anyhow!("Connect timeout")),
+    }
+}
+
+// 丢弃连接中已到达但尚未读取的字节，返回丢弃的字节数
+// 对端已关闭连接时返回错误
+fn discard_pending(stream: &TcpStream) -> std::io::Result<usize> {
+    let mut buffer = [0u8; 256];
+    let mut discarded = 0;
+    loop {
+        match stream.try_read(&mut buffer) {
+            Ok(0) => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => discarded += n,
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => return Ok(discarded),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// Modbus TCP 客户端
+// 通过以太网网关访问设备，请求使用 MBAP 报文头封装，按事务ID匹配响应
+pub struct ModbusTcpClient {
+    address: String, // 服务器地址，例如 "192.168.1.10:502"
+    stream: Mutex<Option<TcpStream>>,
+    // 下一个事务ID，每次请求递增
+    transaction_id: AtomicU16,
+    is_connected: AtomicBool,
+    // 建立连接的超时时间
+    connect_timeout: Duration,
+    // 读写寄存器的响应超时时间，毫秒
+    response_timeout_ms: u64,
+}
+
+impl ModbusTcpClient {
+    // 构造函数
+    // connect_timeout_ms: 建立 TCP 连接的超时时间，毫秒
+    // response_timeout_ms: 读写寄存器的响应超时时间，毫秒
+    pub fn new(address: &str, connect_timeout_ms: u64, response_timeout_ms: u64) -> Arc<Self> {
+        Arc::new(Self {
+            address: address.to_string(),
+            stream: Mutex::new(None),
+            transaction_id: AtomicU16::new(1),
+            is_connected: AtomicBool::new(false),
+            connect_timeout: Duration::from_millis(connect_timeout_ms),
+            response_timeout_ms,
+        })
+    }
+
+    // 生成下一个事务ID
+    fn next_transaction_id(&self) -> u16 {
+        self.transaction_id.fetch_add(1, Ordering::SeqCst)
+    }
+
+    // 使用 MBAP 报文头封装请求
+    fn encode_request(transaction_id: u16, request: &ModbusFrame) -> Vec<u8> {
+        let pdu = request.to_pdu();
+        let mut adu = Vec::with_capacity(MBAP_HEADER_LENGTH + pdu.len());
+        adu.extend_from_slice(&transaction_id.to_be_bytes());
+        adu.extend_from_slice(&0u16.to_be_bytes()); // 协议ID固定为0
+        adu.extend_from_slice(&((pdu.len() + 1) as u16).to_be_bytes()); // 单元ID + PDU
+        adu.push(request.get_slave_address());
+        adu.extend_from_slice(&pdu);
+        adu
+    }
+
+    // 读取一个完整的 MBAP 响应，返回 (事务ID, 单元ID, PDU)
+    async fn read_response(stream: &mut TcpStream) -> anyhow::Result<(u16, u8, Vec<u8>)> {
+        let mut header = [0u8; MBAP_HEADER_LENGTH];
+        stream.read_exact(&mut header).await?;
+
+        let transaction_id = u16::from_be_bytes([header[0], header[1]]);
+        let protocol_id = u16::from_be_bytes([header[2], header[3]]);
+        let length = u16::from_be_bytes([header[4], header[5]]) as usize;
+        let unit_id = header[6];
+
+        if protocol_id != 0 {
+            return Err(anyhow::anyhow!("无效的协议ID: 0x{:04X}", protocol_id));
+        }
+        if length < 2 || length - 1 > MAX_PDU_LENGTH {
+            return Err(anyhow::anyhow!("无效的MBAP长度: {}", length));
+        }
+
+        let mut pdu = vec![0u8; length - 1];
+        stream.read_exact(&mut pdu).await?;
+
+        Ok((transaction_id, unit_id, pdu))
+    }
+
+    // 发送请求并等待事务ID匹配的响应
+    // 事务ID不一致的响应 (例如上一次超时请求的迟到响应) 会被丢弃
+    async fn exchange(
+        stream: &mut TcpStream,
+        transaction_id: u16,
+        adu: &[u8],
+    ) -> anyhow::Result<(u8, Vec<u8>)> {
+        stream.write_all(adu).await?;
+
+        loop {
+            let (response_id, unit_id, pdu) = Self::read_response(stream).await?;
+            if response_id == transaction_id {
+                return Ok((unit_id, pdu));
+            }
+            log::warn!(
+                "丢弃事务ID不匹配的Modbus TCP响应: 预期 {}, 实际 {}",
+                transaction_id,
+                response_id
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl ModbusTransport for ModbusTcpClient {
+    fn get_port(&self) -> &str {
+        &self.address
+    }
+
+    fn is_open(&self) -> bool {
+        self.is_connected.load(Ordering::SeqCst)
+    }
+
+    fn timeout_ms(&self) -> u64 {
+        self.response_timeout_ms
+    }
+
+    async fn open(&self) -> anyhow::Result<()> {
+        let mut stream_guard = self.stream.lock().await;
+        if stream_guard.is_some() {
+            log::info!("Modbus TCP {} 已连接", self.address);
+            self.is_connected.store(true, Ordering::SeqCst);
+            return Ok(());
+        }
+
+        log::info!("尝试连接 Modbus TCP: {}", self.address);
+        match connect(&self.address, self.connect_timeout).await {
+            Ok(stream) => {
+                *stream_guard = Some(stream);
+                self.is_connected.store(true, Ordering::SeqCst);
+                log::info!("Modbus TCP {} 连接成功", self.address);
+                Ok(())
+            }
+            Err(e) => {
+                log::error!("连接 Modbus TCP {} 失败: {}", self.address, e);
+                self.is_connected.store(false, Ordering::SeqCst);
+                Err(e)
+            }
+        }
+    }
+
+    async fn close(&self) {
+        let mut stream_guard = self.stream.lock().await;
+        if stream_guard.take().is_some() {
+            log::info!("关闭 Modbus TCP: {}", self.address);
+        }
+        self.is_connected.store(false, Ordering::SeqCst);
+    }
+
+    async fn transact(
+        &self,
+        transaction: ModbusTransaction,
+        timeout_ms: u64,
+    ) -> anyhow::Result<ModbusFrame> {
+        if !self.is_connected.load(Ordering::SeqCst) {
+            return Err(anyhow::anyhow!("Modbus TCP not connected"));
+        }
+
+        let mut stream_guard = self.stream.lock().await;
+        let Some(stream) = stream_guard.as_mut() else {
+            return Err(anyhow::anyhow!("Modbus TCP not connected"));
+        };
+
+        let transaction_id = self.next_transaction_id();
+        let adu = Self::encode_request(transaction_id, transaction.request());
+        log::info!("发送Modbus TCP请求 ({}): {:02X?}", self.address, adu);
+
+        let timeout = Duration::from_millis(timeout_ms);
+        match time::timeout(timeout, Self::exchange(stream, transaction_id, &adu)).await {
+            Ok(Ok((unit_id, pdu))) => {
+                log::info!(
+                    "接收Modbus TCP响应 ({}): 单元 {} {:02X?}",
+                    self.address,
+                    unit_id,
+                    pdu
+                );
+                Ok(transaction.check_response(unit_id, ModbusFrame::from_pdu(unit_id, &pdu))?)
+            }
+            Ok(Err(e)) => {
+                // 读写错误意味着连接已不可用
+                log::error!("Modbus TCP通信失败 ({}): {}", self.address, e);
+                *stream_guard = None;
+                self.is_connected.store(false, Ordering::SeqCst);
+                Err(e)
+            }
+            Err(_) => {
+                // 超时可能中断在 MBAP 报文中间，剩余字节会破坏后续帧的边界，
+                // 因此丢弃该连接
+                log::warn!("读取Modbus TCP响应超时 ({})", self.address);
+                *stream_guard = None;
+                self.is_connected.store(false, Ordering::SeqCst);
+                Err(ModbusError::Timeout.into())
+            }
+        }
+    }
+}
+
+// RTU over TCP 客户端
+// 串口服务器 (串口转以太网) 透传原始 RTU 帧: 请求带 CRC 原样发送，
+// 响应使用与串口相同的按功能码推算长度的方式组帧
+pub struct RtuOverTcpClient {
+    address: String,   // 服务器地址，例如 "192.168.1.20:4001"
+    port_name: String, // 连接标识，例如 "tcp-rtu://192.168.1.20:4001"
+    stream: Mutex<Option<TcpStream>>,
+    is_connected: AtomicBool,
+    connect_timeout: Duration,
+    response_timeout_ms: u64,
+}
+
+impl RtuOverTcpClient {
+    // 构造函数
+    // connect_timeout_ms: 建立 TCP 连接的超时时间，毫秒
+    // response_timeout_ms: 读写寄存器的响应超时时间，毫秒
+    pub fn new(address: &str, connect_timeout_ms: u64, response_timeout_ms: u64) -> Arc<Self> {
+        Arc::new(Self {
+            address: address.to_string(),
+            port_name: format!("{}{}", TCP_RTU_SCHEME, address),
+            stream: Mutex::new(None),
+            is_connected: AtomicBool::new(false),
+            connect_timeout: Duration::from_millis(connect_timeout_ms),
+            response_timeout_ms,
+        })
+    }
+}
+
+#[async_trait]
+impl ModbusTransport for RtuOverTcpClient {
+    fn get_port(&self) -> &str {
+        &self.port_name
+    }
+
+    fn is_open(&self) -> bool {
+        self.is_connected.load(Ordering::SeqCst)
+    }
+
+    fn timeout_ms(&self) -> u64 {
+        self.response_timeout_ms
+    }
+
+    async fn open(&self) -> anyhow::Result<()> {
+        let mut stream_guard = self.stream.lock().await;
+        if stream_guard.is_some() {
+            log::info!("RTU over TCP {} 已连接", self.address);
+            self.is_connected.store(true, Ordering::SeqCst);
+            return Ok(());
+        }
+
+        log::info!("尝试连接 RTU over TCP: {}", self.address);
+        match connect(&self.address, self.connect_timeout).await {
+            Ok(stream) => {
+                *stream_guard = Some(stream);
+                self.is_connected.store(true, Ordering::SeqCst);
+                log::info!("RTU over TCP {} 连接成功", self.address);
+                Ok(())
+            }
+            Err(e) => {
+                log::error!("连接 RTU over TCP {} 失败: {}", self.address, e);
+                self.is_connected.store(false, Ordering::SeqCst);
+                Err(e)
+            }
+        }
+    }
+
+    async fn close(&self) {
+        let mut stream_guard = self.stream.lock().await;
+        if stream_guard.take().is_some() {
+            log::info!("关闭 RTU over TCP: {}", self.address);
+        }
+        self.is_connected.store(false, Ordering::SeqCst);
+    }
+
+    async fn transact(
+        &self,
+        transaction: ModbusTransaction,
+        timeout_ms: u64,
+    ) -> anyhow::Result<ModbusFrame> {
+        if !self.is_connected.load(Ordering::SeqCst) {
+            return Err(anyhow::anyhow!("RTU over TCP not connected"));
+        }
+
+        let mut stream_guard = self.stream.lock().await;
+        let Some(stream) = stream_guard.as_mut() else {
+            return Err(anyhow::anyhow!("RTU over TCP not connected"));
+        };
+
+        // RTU 帧没有事务ID，先丢弃上一次超时请求的迟到响应，避免与本次请求配对
+        match discard_pending(stream) {
+            Ok(0) => {}
+            Ok(n) => log::warn!("丢弃RTU over TCP残留数据 {} 字节 ({})", n, self.address),
+            Err(e) => {
+                log::error!("RTU over TCP连接已断开 ({}): {}", self.address, e);
+                *stream_guard = None;
+                self.is_connected.store(false, Ordering::SeqCst);
+                return Err(e.into());
+            }
+        }
+
+        let command = transaction.request().to_bytes();
+        log::info!("发送RTU over TCP命令 ({}): {:02X?}", self.address, command);
+
+        let timeout = Duration::from_millis(timeout_ms);
+        let result = time::timeout(timeout, async {
+            stream.write_all(&command).await?;
+            read_rtu_frame(stream, NETWORK_FRAME_SILENCE).await
+        })
+        .await;
+
+        match result {
+            Ok(Ok(response)) => {
+                log::info!("接收RTU over TCP响应 ({}): {:02X?}", self.address, response);
+                Ok(transaction.parse_response(&response)?)
+            }
+            Ok(Err(e)) => {
+                // 读写错误意味着连接已不可用
+                log::error!("RTU over TCP通信失败 ({}): {}", self.address, e);
+                *stream_guard = None;
+                self.is_connected.store(false, Ordering::SeqCst);
+                Err(e)
+            }
+            Err(_) => {
+                log::warn!("读取RTU over TCP响应超时 ({})", self.address);
+                Err(ModbusError::Timeout.into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    // 本地 Modbus TCP 替身服务器: 对每个读请求返回 "寄存器地址 + 偏移" 作为寄存器值，
+    // 对写请求原样回显；stale_first 为 true 时在每个响应前先发送一个过期事务ID的响应
+    async fn spawn_stand_in_server(stale_first: bool) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            loop {
+                let (transaction_id, unit_id, pdu) =
+                    match ModbusTcpClient::read_response(&mut socket).await {
+                        Ok(request) => request,
+                        Err(_) => break,
+                    };
+
+                let response_pdu = match pdu[0] {
+                    0x03 => {
+                        let start = u16::from_be_bytes([pdu[1], pdu[2]]);
+                        let count = u16::from_be_bytes([pdu[3], pdu[4]]);
+                        let mut response = vec![0x03, (count * 2) as u8];
+                        for offset in 0..count {
+                            response.extend_from_slice(&(start + offset).to_be_bytes());
+                        }
+                        response
+                    }
+                    0x06 | 0x10 => pdu[..5].to_vec(),
+                    code => vec![code | 0x80, 0x01],
+                };
+
+                let mut ids = vec![transaction_id];
+                if stale_first {
+                    ids.insert(0, transaction_id.wrapping_sub(1));
+                }
+                for id in ids {
+                    let frame =
+                        ModbusFrame::new(unit_id, response_pdu[0], response_pdu[1..].to_vec());
+                    let adu = ModbusTcpClient::encode_request(id, &frame);
+                    socket.write_all(&adu).await.unwrap();
+                }
+            }
+        });
+
+        address
+    }
+
+    // 本地串口服务器替身: 读请求返回寄存器地址作为值，并把响应拆成多段发送
+    // 第一个请求的响应延迟 late_first 后才发送
+    async fn spawn_rtu_stand_in_server_with_delay(late_first: Duration) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buffer = [0u8; 8];
+            let mut delay = late_first;
+            while socket.read_exact(&mut buffer).await.is_ok() {
+                tokio::time::sleep(std::mem::take(&mut delay)).await;
+                let request = ModbusFrame::from_bytes(&buffer).unwrap();
+                let data = request.get_data();
+                let start = u16::from_be_bytes([data[0], data[1]]);
+                let count = u16::from_be_bytes([data[2], data[3]]);
+
+                let mut payload = vec![(count * 2) as u8];
+                for offset in 0..count {
+                    payload.extend_from_slice(&(start + offset).to_be_bytes());
+                }
+                let response = ModbusFrame::new(request.get_slave_address(), 0x03, payload);
+
+                for chunk in response.to_bytes().chunks(3) {
+                    socket.write_all(chunk).await.unwrap();
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+            }
+        });
+
+        address
+    }
+
+    async fn spawn_rtu_stand_in_server() -> String {
+        spawn_rtu_stand_in_server_with_delay(Duration::ZERO).await
+    }
+
+    #[tokio::test]
+    async fn test_read_over_rtu_tcp() {
+        let address = spawn_rtu_stand_in_server().await;
+        let client = RtuOverTcpClient::new(&address, 1000, 1000);
+        assert_eq!(client.get_port(), format!("tcp-rtu://{}", address));
+
+        client.open().await.unwrap();
+        let values = client.read_registers(1, 0x4001, 3).await.unwrap();
+        assert_eq!(values, vec![0x4001, 0x4002, 0x4003]);
+
+        let values = client.read_registers(1, 0x1000, 10).await.unwrap();
+        assert_eq!(values, (0x1000..0x100A).collect::<Vec<u16>>());
+    }
+
+    #[tokio::test]
+    async fn test_discard_late_rtu_response() {
+        let address = spawn_rtu_stand_in_server_with_delay(Duration::from_millis(150)).await;
+        let client = RtuOverTcpClient::new(&address, 1000, 50);
+        client.open().await.unwrap();
+
+        // 第一个请求超时，其响应在下一个请求之前到达
+        assert!(client.read_registers(1, 0x4001, 1).await.is_err());
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        // 迟到的响应被丢弃，不会作为本次请求的响应
+        let values = client.read_registers(1, 0x4002, 1).await.unwrap();
+        assert_eq!(values, vec![0x4002]);
+        assert!(client.is_open());
+    }
+
+    #[test]
+    fn test_encode_mbap_header() {
+        let request = ModbusFrame::new_write_single_register(0x11, 0x0001, 0x0003);
+        let adu = ModbusTcpClient::encode_request(0x1234, &request);
+        assert_eq!(
+            adu,
+            vec![
+                0x12, 0x34, 0x00, 0x00, 0x00, 0x06, 0x11, 0x06, 0x00, 0x01, 0x00, 0x03
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_write_over_tcp() {
+        let address = spawn_stand_in_server(false).await;
+        let client = ModbusTcpClient::new(&address, 1000, 1000);
+        client.open().await.unwrap();
+        assert!(client.is_open());
+
+        let values = client.read_registers(1, 0x4001, 3).await.unwrap();
+        assert_eq!(values, vec![0x4001, 0x4002, 0x4003]);
+
+        client.write_register(1, 0x4002, 1).await.unwrap();
+        client.write_registers(1, 0x1000, &[1, 2, 3]).await.unwrap();
+
+        client.close().await;
+        assert!(!client.is_open());
+    }
+
+    #[tokio::test]
+    async fn test_drop_connection_on_timeout() {
+        // 只接收请求、不返回响应的服务器
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buffer = [0u8; 64];
+            while matches!(socket.read(&mut buffer).await, Ok(n) if n > 0) {}
+        });
+
+        let client = ModbusTcpClient::new(&address, 1000, 50);
+        client.open().await.unwrap();
+        let error = client.read_registers(1, 0x4001, 1).await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<ModbusError>(),
+            Some(ModbusError::Timeout)
+        ));
+        assert!(!client.is_open());
+    }
+
+    #[tokio::test]
+    async fn test_discard_stale_transaction() {
+        let address = spawn_stand_in_server(true).await;
+        let client = ModbusTcpClient::new(&address, 1000, 1000);
+        client.open().await.unwrap();
+
+        // 每次请求前都有一个过期事务ID的响应，应被丢弃
+        for _ in 0..3 {
+            let values = client.read_registers(1, 0xC000, 1).await.unwrap();
+            assert_eq!(values, vec![0xC000]);
+        }
+    }
+}
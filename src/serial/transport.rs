@@ -0,0 +1,237 @@
+use async_trait::async_trait;
+
+use crate::serial::modbus::{
+    MAX_READ_REGISTERS, ModbusError, ModbusFrame, ModbusTransaction, RegisterType,
+};
+use crate::serial::simulator::SIMULATOR_SCHEME;
+
+// 传输层未指定时使用的响应超时时间，毫秒
+pub const DEFAULT_RESPONSE_TIMEOUT_MS: u64 = 1000;
+
+// Modbus 传输层抽象
+// 串口 (RTU) 和 Modbus TCP 等传输方式都实现该 trait，
+// 上层的芯片检测和读写寄存器逻辑只依赖这里提供的接口
+#[async_trait]
+pub trait ModbusTransport: Send + Sync {
+    // 连接标识，例如 "COM3"、"/dev/ttyUSB0" 或 "192.168.1.10:502"
+    fn get_port(&self) -> &str;
+
+    // 检查连接当前是否打开
+    fn is_open(&self) -> bool;
+
+    // 响应超时时间，毫秒；下面的读写寄存器方法使用该超时
+    fn timeout_ms(&self) -> u64 {
+        DEFAULT_RESPONSE_TIMEOUT_MS
+    }
+
+    // 打开连接
+    async fn open(&self) -> anyhow::Result<()>;
+
+    // 关闭连接
+    async fn close(&self);
+
+    // 发送请求并返回经过配对校验的响应帧
+    async fn transact(
+        &self,
+        transaction: ModbusTransaction,
+        timeout_ms: u64,
+    ) -> anyhow::Result<ModbusFrame>;
+
+    // 读取连续的保持寄存器 (功能码 0x03)，返回解码后的寄存器值
+    async fn read_registers(
+        &self,
+        slave_address: u8,
+        start_address: u16,
+        count: u16,
+    ) -> anyhow::Result<Vec<u16>> {
+        if count == 0 || count > MAX_READ_REGISTERS {
+            return Err(ModbusError::InvalidQuantity {
+                quantity: count as usize,
+                max: MAX_READ_REGISTERS as usize,
+            }
+            .into());
+        }
+
+        let request = ModbusFrame::new_read_request(
+            slave_address,
+            RegisterType::HoldingRegister,
+            start_address,
+            count,
+        )?;
+
+        // 校验响应的从站地址、功能码和字节数
+        let response = self
+            .transact(ModbusTransaction::new(request), self.timeout_ms())
+            .await?;
+        Ok(response.get_registers()?)
+    }
+
+    // 写单个保持寄存器 (功能码 0x06)
+    async fn write_register(
+        &self,
+        slave_address: u8,
+        address: u16,
+        value: u16,
+    ) -> anyhow::Result<()> {
+        let request = ModbusFrame::new_write_single_register(slave_address, address, value);
+        // 回显的地址和值一致才视为写入成功
+        self.transact(ModbusTransaction::new(request), self.timeout_ms())
+            .await?;
+        Ok(())
+    }
+
+    // 写连续的多个保持寄存器 (功能码 0x10)，一次最多123个
+    async fn write_registers(
+        &self,
+        slave_address: u8,
+        start_address: u16,
+        values: &[u16],
+    ) -> anyhow::Result<()> {
+        let request =
+            ModbusFrame::new_write_multiple_registers(slave_address, start_address, values)?;
+        // 回显的起始地址和数量一致才视为写入成功
+        self.transact(ModbusTransaction::new(request), self.timeout_ms())
+            .await?;
+        Ok(())
+    }
+}
+
+// RTU over TCP 连接的 URL 前缀
+pub const TCP_RTU_SCHEME: &str = "tcp-rtu://";
+
+// 连接目标，由界面上的端口输入解析得到
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionTarget {
+    // 本地串口，例如 "COM3" 或 "/dev/ttyUSB0"
+    Serial(String),
+    // Modbus TCP 服务器，例如 "192.168.1.10:502"
+    Tcp(String),
+    // 串口服务器透传 RTU 帧，例如 "tcp-rtu://192.168.1.20:4001" 解析为 "192.168.1.20:4001"
+    TcpRtu(String),
+    // 进程内模拟器，保留完整输入，例如 "sim://demo.csv"
+    Simulator(String),
+}
+
+impl ConnectionTarget {
+    // 解析端口输入: COMx 和 /dev/tty* 视为串口，host:port 视为 Modbus TCP，
+    // tcp-rtu://host:port 视为 RTU over TCP，sim:// 视为进程内模拟器
+    pub fn parse(input: &str) -> Self {
+        if let Some(address) = input.strip_prefix(TCP_RTU_SCHEME) {
+            return Self::TcpRtu(address.to_string());
+        }
+        if input.starts_with(SIMULATOR_SCHEME) {
+            return Self::Simulator(input.to_string());
+        }
+
+        let is_serial = input.to_uppercase().starts_with("COM") || input.starts_with("/dev/");
+        if !is_serial
+            && let Some((host, port)) = input.rsplit_once(':')
+            && !host.is_empty()
+            && port.parse::<u16>().is_ok()
+        {
+            return Self::Tcp(input.to_string());
+        }
+
+        Self::Serial(input.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::mock::MockTransport;
+    use std::sync::Mutex;
+
+    // 记录每次事务使用的超时时间
+    struct TimeoutRecorder {
+        inner: MockTransport,
+        timeout_ms: u64,
+        used: Mutex<Vec<u64>>,
+    }
+
+    #[async_trait]
+    impl ModbusTransport for TimeoutRecorder {
+        fn get_port(&self) -> &str {
+            self.inner.get_port()
+        }
+
+        fn is_open(&self) -> bool {
+            self.inner.is_open()
+        }
+
+        fn timeout_ms(&self) -> u64 {
+            self.timeout_ms
+        }
+
+        async fn open(&self) -> anyhow::Result<()> {
+            self.inner.open().await
+        }
+
+        async fn close(&self) {
+            self.inner.close().await
+        }
+
+        async fn transact(
+            &self,
+            transaction: ModbusTransaction,
+            timeout_ms: u64,
+        ) -> anyhow::Result<ModbusFrame> {
+            self.used.lock().unwrap().push(timeout_ms);
+            self.inner.transact(transaction, timeout_ms).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_access_uses_transport_timeout() {
+        let transport = TimeoutRecorder {
+            inner: MockTransport::new("mock", 0x01).with_registers([(0x1000, 0x12)]),
+            timeout_ms: 250,
+            used: Mutex::new(Vec::new()),
+        };
+        transport.open().await.unwrap();
+
+        transport.read_registers(0x01, 0x1000, 1).await.unwrap();
+        transport.write_register(0x01, 0x1000, 0x34).await.unwrap();
+        transport
+            .write_registers(0x01, 0x1000, &[0x56])
+            .await
+            .unwrap();
+        assert_eq!(*transport.used.lock().unwrap(), vec![250, 250, 250]);
+
+        // 未指定时使用默认超时
+        assert_eq!(transport.inner.timeout_ms(), DEFAULT_RESPONSE_TIMEOUT_MS);
+    }
+
+    #[test]
+    fn test_parse_connection_target() {
+        assert_eq!(
+            ConnectionTarget::parse("COM7"),
+            ConnectionTarget::Serial("COM7".to_string())
+        );
+        assert_eq!(
+            ConnectionTarget::parse("/dev/ttyUSB0"),
+            ConnectionTarget::Serial("/dev/ttyUSB0".to_string())
+        );
+        assert_eq!(
+            ConnectionTarget::parse("192.168.1.10:502"),
+            ConnectionTarget::Tcp("192.168.1.10:502".to_string())
+        );
+        assert_eq!(
+            ConnectionTarget::parse("gateway.local:1502"),
+            ConnectionTarget::Tcp("gateway.local:1502".to_string())
+        );
+        assert_eq!(
+            ConnectionTarget::parse("tcp-rtu://192.168.1.20:4001"),
+            ConnectionTarget::TcpRtu("192.168.1.20:4001".to_string())
+        );
+        assert_eq!(
+            ConnectionTarget::parse("sim://demo.csv"),
+            ConnectionTarget::Simulator("sim://demo.csv".to_string())
+        );
+        // 端口号无效时按串口处理
+        assert_eq!(
+            ConnectionTarget::parse("host:abc"),
+            ConnectionTarget::Serial("host:abc".to_string())
+        );
+    }
+}
@@ -0,0 +1,402 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::anyhow;
+use serde::Serialize;
+
+use crate::csv_handler::RegisterRecord;
+use crate::device_io::register_mask;
+use crate::export::{self, Snapshot};
+use crate::register_map::parse_number;
+use crate::register_store::RegisterKey;
+
+// 比较的一侧: 导出的快照 (读回的值) 或寄存器表 (设置值)
+pub struct CompareSource {
+    // 界面和报告中显示的来源，例如文件名或 "设置值"
+    pub label: String,
+    // 各寄存器及参与比较的值，没有值 (未读取) 时为 None
+    rows: Vec<(RegisterRecord, Option<u32>)>,
+}
+
+impl CompareSource {
+    // 快照中读回的值
+    pub fn from_snapshot(label: &str, snapshot: &Snapshot) -> anyhow::Result<Self> {
+        let mut rows = Vec::new();
+        for register in &snapshot.registers {
+            let record = register.to_record()?;
+            let value = parse_value(&record, register.read_value.as_deref())?;
+            rows.push((record, value));
+        }
+        Ok(Self {
+            label: label.to_string(),
+            rows,
+        })
+    }
+
+    // 读取快照文件，来源显示为文件名和快照时间
+    pub fn load(file_path: &Path) -> anyhow::Result<Self> {
+        let snapshot = Snapshot::load(file_path)
+            .map_err(|e| anyhow!("读取快照 {:?} 失败: {}", file_path, e))?;
+        let name = file_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let label = if snapshot.timestamp.is_empty() {
+            name
+        } else {
+            format!("{} ({})", name, snapshot.timestamp)
+        };
+        Self::from_snapshot(&label, &snapshot)
+    }
+
+    // 寄存器表的设置值 (Value 列) 作为标准值
+    pub fn from_golden(label: &str, records: &[RegisterRecord]) -> anyhow::Result<Self> {
+        let mut rows = Vec::new();
+        for record in records {
+            let value = parse_value(record, Some(&record.value))?;
+            rows.push((record.clone(), value));
+        }
+        Ok(Self {
+            label: label.to_string(),
+            rows,
+        })
+    }
+}
+
+fn parse_value(record: &RegisterRecord, value: Option<&str>) -> anyhow::Result<Option<u32>> {
+    match value.map(str::trim) {
+        Some(value) if !value.is_empty() => parse_number(value)
+            .map(Some)
+            .map_err(|e| anyhow!("寄存器 {} 的值无效: {}", record.register, e)),
+        _ => Ok(None),
+    }
+}
+
+// 差异类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum DiffKind {
+    // 两侧都有，(掩码内的) 值不同
+    Changed,
+    // 只在基准中
+    Missing,
+    // 只在比较的一侧中
+    Extra,
+}
+
+impl std::fmt::Display for DiffKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Changed => write!(f, "不同"),
+            Self::Missing => write!(f, "缺少"),
+            Self::Extra => write!(f, "多出"),
+        }
+    }
+}
+
+// 差异中的一行，值按寄存器宽度显示，没有值时为空
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffEntry {
+    pub page_addr: String,
+    pub register: String,
+    pub kind: DiffKind,
+    pub base_value: String,
+    pub other_value: String,
+    // 比较时使用的掩码，比较全部位时为空
+    pub mask: String,
+}
+
+// 比较结果汇总
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CompareSummary {
+    // 两侧都有的寄存器数
+    pub compared: usize,
+    pub changed: usize,
+    pub missing: usize,
+    pub extra: usize,
+}
+
+impl CompareSummary {
+    pub fn is_identical(&self) -> bool {
+        self.changed == 0 && self.missing == 0 && self.extra == 0
+    }
+}
+
+impl std::fmt::Display for CompareSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "比较 {} 个寄存器: 不同 {}，缺少 {}，多出 {}",
+            self.compared, self.changed, self.missing, self.extra
+        )
+    }
+}
+
+// 两侧寄存器的差异，按地址排序
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SnapshotDiff {
+    pub base: String,
+    pub other: String,
+    pub use_masks: bool,
+    pub summary: CompareSummary,
+    pub entries: Vec<DiffEntry>,
+}
+
+// 按地址 + 名称对应两侧的寄存器；use_masks 为 true 时只比较基准中各寄存器
+// 掩码 (Mask 列，没有时为可写的位) 内的位
+pub fn compare(base: &CompareSource, other: &CompareSource, use_masks: bool) -> SnapshotDiff {
+    let other_rows: BTreeMap<RegisterKey, &(RegisterRecord, Option<u32>)> = other
+        .rows
+        .iter()
+        .map(|row| (RegisterKey::of(&row.0), row))
+        .collect();
+    let base_rows: BTreeMap<RegisterKey, &(RegisterRecord, Option<u32>)> = base
+        .rows
+        .iter()
+        .map(|row| (RegisterKey::of(&row.0), row))
+        .collect();
+
+    let format = |record: &RegisterRecord, value: Option<u32>| {
+        value
+            .map(|value| record.def.format_value(value))
+            .unwrap_or_default()
+    };
+
+    let mut entries = BTreeMap::new();
+    let mut summary = CompareSummary::default();
+    for (key, (record, base_value)) in &base_rows {
+        let Some((other_record, other_value)) = other_rows.get(key) else {
+            summary.missing += 1;
+            entries.insert(
+                key.clone(),
+                DiffEntry {
+                    page_addr: record.page_addr.clone(),
+                    register: record.register.clone(),
+                    kind: DiffKind::Missing,
+                    base_value: format(record, *base_value),
+                    other_value: String::new(),
+                    mask: String::new(),
+                },
+            );
+            continue;
+        };
+
+        summary.compared += 1;
+        let full_mask = record.def.width.full_mask();
+        let mask = if use_masks {
+            register_mask(record).unwrap_or(full_mask)
+        } else {
+            full_mask
+        };
+        let changed = match (base_value, other_value) {
+            (Some(base_value), Some(other_value)) => (base_value ^ other_value) & mask != 0,
+            (None, None) => false,
+            _ => true,
+        };
+        if changed {
+            summary.changed += 1;
+            entries.insert(
+                key.clone(),
+                DiffEntry {
+                    page_addr: record.page_addr.clone(),
+                    register: record.register.clone(),
+                    kind: DiffKind::Changed,
+                    base_value: format(record, *base_value),
+                    other_value: format(other_record, *other_value),
+                    mask: if mask == full_mask {
+                        String::new()
+                    } else {
+                        record.def.format_value(mask)
+                    },
+                },
+            );
+        }
+    }
+
+    for (key, (record, other_value)) in &other_rows {
+        if base_rows.contains_key(key) {
+            continue;
+        }
+        summary.extra += 1;
+        entries.insert(
+            key.clone(),
+            DiffEntry {
+                page_addr: record.page_addr.clone(),
+                register: record.register.clone(),
+                kind: DiffKind::Extra,
+                base_value: String::new(),
+                other_value: format(record, *other_value),
+                mask: String::new(),
+            },
+        );
+    }
+
+    SnapshotDiff {
+        base: base.label.clone(),
+        other: other.label.clone(),
+        use_masks,
+        summary,
+        entries: entries.into_values().collect(),
+    }
+}
+
+impl SnapshotDiff {
+    // 默认的报告文件名，例如 "compare_20240501_140327.csv"
+    pub fn report_file_name(&self) -> String {
+        format!(
+            "compare_{}.csv",
+            chrono::Local::now().format("%Y%m%d_%H%M%S")
+        )
+    }
+
+    // 每个差异一行，并记录两侧的来源
+    pub fn to_report_csv(&self) -> anyhow::Result<String> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record([
+            "Page_Addr",
+            "Register",
+            "Diff",
+            "Base_Value",
+            "Compare_Value",
+            "Mask",
+            "Base",
+            "Compare",
+        ])?;
+        for entry in &self.entries {
+            writer.write_record([
+                entry.page_addr.as_str(),
+                entry.register.as_str(),
+                &entry.kind.to_string(),
+                entry.base_value.as_str(),
+                entry.other_value.as_str(),
+                entry.mask.as_str(),
+                self.base.as_str(),
+                self.other.as_str(),
+            ])?;
+        }
+
+        let bytes = writer.into_inner().map_err(|e| anyhow!("{}", e))?;
+        Ok(String::from_utf8(bytes)?)
+    }
+
+    // 按扩展名保存为 JSON 或 CSV 报告
+    pub fn save_report(&self, file_path: &Path) -> anyhow::Result<()> {
+        export::save_csv_or_json(
+            file_path,
+            || self.to_report_csv(),
+            || Ok(serde_json::to_string_pretty(self)?),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv_handler::CsvHandler;
+    use tempfile::NamedTempFile;
+
+    fn golden() -> Vec<RegisterRecord> {
+        let csv = NamedTempFile::new().unwrap();
+        std::fs::write(
+            csv.path(),
+            "Page_Addr,Register,R_W,Value,Mask\n\
+             0x4000,CHIPID,R,0x1C,\n\
+             0x4001,IO1,RW,0x01,0x01\n\
+             0x4002,IO2,RW,0x00,\n\
+             0x4010,CTRL,RW,0x05,\n",
+        )
+        .unwrap();
+        CsvHandler::parse_csv_file(csv.path()).unwrap()
+    }
+
+    // 从 golden 导出的快照，读回的值由 values 给出
+    fn dump(values: &[(&str, Option<&str>)]) -> Snapshot {
+        let mut records = golden();
+        for record in records.iter_mut() {
+            record.w_value = values
+                .iter()
+                .find(|(name, _)| *name == record.register)
+                .and_then(|(_, value)| value.map(str::to_string));
+        }
+        records.retain(|record| values.iter().any(|(name, _)| *name == record.register));
+        Snapshot::capture(&records, "COM7", "MALD rev 1")
+    }
+
+    #[test]
+    fn test_compare_dump_with_golden() {
+        let golden = CompareSource::from_golden("设置值", &golden()).unwrap();
+        // IO1 只有最低位在掩码内，CTRL 不在快照中
+        let snapshot = dump(&[
+            ("CHIPID", Some("0x1C")),
+            ("IO1", Some("0xF1")),
+            ("IO2", Some("0x03")),
+        ]);
+        let dump = CompareSource::from_snapshot("board.csv", &snapshot).unwrap();
+
+        let diff = compare(&golden, &dump, true);
+        assert_eq!(
+            diff.summary,
+            CompareSummary {
+                compared: 3,
+                changed: 1,
+                missing: 1,
+                extra: 0
+            }
+        );
+        let rows: Vec<(&str, DiffKind, &str, &str)> = diff
+            .entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.register.as_str(),
+                    entry.kind,
+                    entry.base_value.as_str(),
+                    entry.other_value.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("IO2", DiffKind::Changed, "0x00", "0x03"),
+                ("CTRL", DiffKind::Missing, "0x05", ""),
+            ]
+        );
+
+        // 不使用掩码时 IO1 的高位也比较
+        let diff = compare(&golden, &dump, false);
+        assert_eq!(diff.summary.changed, 2);
+        assert_eq!(diff.entries[0].register, "IO1");
+        assert_eq!(diff.entries[0].mask, "");
+    }
+
+    #[test]
+    fn test_compare_two_dumps_and_report() {
+        let good = dump(&[("CHIPID", Some("0x1C")), ("IO1", Some("0x01"))]);
+        let bad = dump(&[
+            ("CHIPID", Some("0x1C")),
+            ("IO1", None),
+            ("CTRL", Some("0x05")),
+        ]);
+        let good = CompareSource::from_snapshot("good.json", &good).unwrap();
+        let bad = CompareSource::from_snapshot("bad.json", &bad).unwrap();
+
+        let diff = compare(&good, &bad, true);
+        assert_eq!(
+            diff.summary.to_string(),
+            "比较 2 个寄存器: 不同 1，缺少 0，多出 1"
+        );
+        assert!(!diff.summary.is_identical());
+        assert!(compare(&good, &good, true).summary.is_identical());
+
+        let report = diff.to_report_csv().unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Page_Addr,Register,Diff,Base_Value,Compare_Value,Mask,Base,Compare",
+                "0x4001,IO1,不同,0x01,,0x01,good.json,bad.json",
+                "0x4010,CTRL,多出,,0x05,,good.json,bad.json",
+            ]
+        );
+    }
+}
@@ -1,13 +1,44 @@
+use lazy_static::lazy_static;
 use slint::{ComponentHandle, Weak};
+use std::collections::HashSet;
+use std::sync::Arc;
 use std::time::Duration;
 
-use crate::chip_detection::detect_all_chips;
-use crate::csv_handler::CsvHandler;
+use crate::chip_detection::{
+    ChipType, SCAN_TIMEOUT_MS, SLAVE_ADDRESS_RANGE, detect_all_chips, scan_bus,
+};
+use crate::csv_handler::{CsvHandler, RegisterRecord, RowStatus};
+use crate::device_io::{
+    self, CHIP1_IO_BASE, CHIP2_IO_BASE, VerifyOutcome, VerifyResult, VerifySummary, WriteDiff,
+};
+use crate::export::{self, Snapshot};
+use crate::job::{self, Job, JobKind};
+use crate::register_store::RegisterKey;
 use crate::serial::manager::SerialPortRegistry;
-use crate::serial::modbus::{ModbusFrame, RegisterType};
+use crate::serial::modbus::{Framing, ModbusError};
+use crate::serial::retry::{Retried, with_retry};
+use crate::serial::rs485::Rs485Config;
+use crate::serial::scheduler::{Priority, QueueDepth};
+use crate::serial::settings::SerialSettings;
+use crate::serial::transport::{ConnectionTarget, DEFAULT_RESPONSE_TIMEOUT_MS, ModbusTransport};
+use crate::snapshot_compare::{self, CompareSource, SnapshotDiff};
 use crate::{AppState, AppWindow};
 use crate::{config, csv_handler};
 
+// 等待用户确认的写入预览
+struct PendingWrite {
+    port_path: String,
+    diffs: Vec<WriteDiff>,
+}
+
+lazy_static! {
+    static ref PENDING_WRITE: tokio::sync::Mutex<Option<PendingWrite>> =
+        tokio::sync::Mutex::new(None);
+    // 最近一次快照比较的结果，用于导出报告
+    static ref LAST_COMPARE: tokio::sync::Mutex<Option<SnapshotDiff>> =
+        tokio::sync::Mutex::new(None);
+}
+
 pub fn setup_ui_handlers(ui: &AppWindow) {
     // 连接按钮点击事件
     {
@@ -17,6 +48,14 @@ pub fn setup_ui_handlers(ui: &AppWindow) {
         });
     }
 
+    // 探测按钮点击事件
+    {
+        let ui_weak = ui.as_weak();
+        ui.global::<AppState>().on_probe_clicked(move || {
+            start_probe(ui_weak.clone());
+        });
+    }
+
     // 端口变更事件
     {
         ui.global::<AppState>().on_port_changed(move |new_port| {
@@ -33,6 +72,14 @@ pub fn setup_ui_handlers(ui: &AppWindow) {
             });
     }
 
+    // 总线扫描按钮点击事件
+    {
+        let ui_weak = ui.as_weak();
+        ui.global::<AppState>().on_scan_bus_clicked(move || {
+            handle_scan_bus_click(ui_weak.clone());
+        });
+    }
+
     // 读取文件按钮点击事件
     {
         let ui_weak = ui.as_weak();
@@ -57,6 +104,62 @@ pub fn setup_ui_handlers(ui: &AppWindow) {
         });
     }
 
+    // 确认写入按钮点击事件
+    {
+        let ui_weak = ui.as_weak();
+        ui.global::<AppState>().on_confirm_write_clicked(move || {
+            handle_confirm_write_click(ui_weak.clone());
+        });
+    }
+
+    // 取消写入按钮点击事件
+    {
+        let ui_weak = ui.as_weak();
+        ui.global::<AppState>().on_cancel_write_clicked(move || {
+            handle_cancel_write_click(ui_weak.clone());
+        });
+    }
+
+    // 导出按钮点击事件
+    {
+        let ui_weak = ui.as_weak();
+        ui.global::<AppState>().on_export_clicked(move || {
+            handle_export_click(ui_weak.clone());
+        });
+    }
+
+    // 快照比较按钮点击事件
+    {
+        let ui_weak = ui.as_weak();
+        ui.global::<AppState>().on_compare_clicked(move || {
+            handle_compare_click(ui_weak.clone());
+        });
+    }
+
+    // 导出比较报告按钮点击事件
+    {
+        let ui_weak = ui.as_weak();
+        ui.global::<AppState>().on_export_report_clicked(move || {
+            handle_export_report_click(ui_weak.clone());
+        });
+    }
+
+    // 关闭比较结果按钮点击事件
+    {
+        let ui_weak = ui.as_weak();
+        ui.global::<AppState>().on_close_compare_clicked(move || {
+            handle_close_compare_click(ui_weak.clone());
+        });
+    }
+
+    // 停止按钮点击事件
+    {
+        let ui_weak = ui.as_weak();
+        ui.global::<AppState>().on_stop_job_clicked(move || {
+            handle_stop_job_click(ui_weak.clone());
+        });
+    }
+
     // 读取地址按钮点击事件
     {
         let ui_weak = ui.as_weak();
@@ -78,68 +181,76 @@ fn start_connect(ui_weak: Weak<AppWindow>) {
     let ui_weak2 = ui_weak.clone();
     if let Some(ui) = ui_weak2.upgrade() {
         // 使用config::get_runtime()而不是创建新的runtime
-        let port = ui.global::<AppState>().get_port_value().to_string();
-        log::info!("开始连接... {}", port);
+        let state = ui.global::<AppState>();
+        let port = state.get_port_value().to_string();
+        let framing = Framing::from_name(&state.get_framing_value());
+        let settings = parse_serial_settings(&state);
+        log::info!("开始连接... {} ({:?})", port, framing);
 
         config::get_runtime().spawn(async move {
-            handle_connect_click(ui_weak2, port).await;
+            handle_connect_click(ui_weak2, port, settings, framing).await;
         });
     }
 }
 
-async fn handle_connect_click(ui_weak: Weak<AppWindow>, port: String) {
+// 读取连接面板中的串口线路参数和 RS-485 参数
+fn parse_serial_settings(state: &AppState) -> anyhow::Result<SerialSettings> {
+    let rs485 = Rs485Config::parse(
+        &state.get_rs485_direction_value(),
+        &state.get_rs485_pre_delay_value(),
+        &state.get_rs485_post_delay_value(),
+        state.get_rs485_strip_echo(),
+    )?;
+    SerialSettings::parse(
+        &state.get_baud_value(),
+        &state.get_data_bits_value(),
+        &state.get_parity_value(),
+        &state.get_stop_bits_value(),
+        &state.get_flow_control_value(),
+        &state.get_timeout_value(),
+        rs485,
+    )
+}
+
+async fn handle_connect_click(
+    ui_weak: Weak<AppWindow>,
+    port: String,
+    settings: anyhow::Result<SerialSettings>,
+    framing: Framing,
+) {
     let registry = SerialPortRegistry::get_global().await;
 
-    if registry.get_port(&port).await.is_none() {
+    if registry.get_transport(&port).await.is_none() {
         // 尝试连接
-        log::info!("尝试连接串口: {}", port);
+        log::info!("尝试连接: {}", port);
 
         // 更新UI状态 - 连接中
         update_ui_status(&ui_weak, "连接中...", "连接中", false, false).await;
 
         // 使用SerialPortRegistry::get_global()
         let registry = SerialPortRegistry::get_global().await;
-        match registry.add_port_with_defaults(&port).await {
-            Ok(_) => {
-                update_ui_status(&ui_weak, "连接中", "断开", true, false).await;
-
-                registry.open_all().await;
-
-                tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-                if !registry.is_connected(&port).await {
-                    log::error!("串口连接失败: {}", port);
-                    update_ui_status(&ui_weak, "连接失败", "连接", false, false).await;
-                    registry.remove_port(&port).await;
-                    return;
-                }
-
-                log::info!("串口连接成功: {}", port);
-                // 更新UI状态 - 已连接
-                update_ui_status(&ui_weak, "已连接", "断开", true, false).await;
-
-                // 等待一段时间确保连接稳定
-
-                // 开始芯片检测
-                if let Some(port_manager) = registry.get_port(&port).await {
-                    log::info!("开始检测芯片类型...");
-
-                    let (chip1_type, chip2_type) = detect_all_chips(port_manager).await;
-
-                    let chip1_str = chip1_type.to_string();
-                    let chip2_str = chip2_type.to_string();
-
-                    // 更新芯片信息
-                    update_chip_info(&ui_weak, &chip1_str, &chip2_str).await;
-
-                    log::info!(
-                        "芯片检测完成: 芯片1={:?}, 芯片2={:?}",
-                        chip1_type,
-                        chip2_type
-                    );
-
-                    // 启动IO状态轮询
-                    start_io_polling(ui_weak.clone(), port.clone()).await;
+        // 根据端口输入选择串口或 Modbus TCP，帧格式仅对串口生效，超时时间对 TCP 同样生效
+        let timeout_ms = settings
+            .as_ref()
+            .map_or(DEFAULT_RESPONSE_TIMEOUT_MS, |settings| settings.timeout_ms);
+        let added = match ConnectionTarget::parse(&port) {
+            ConnectionTarget::Serial(path) => match settings {
+                Ok(settings) => {
+                    registry
+                        .add_port_with_defaults(&path, settings, framing)
+                        .await
                 }
+                Err(e) => Err(e),
+            },
+            ConnectionTarget::Tcp(address) => registry.add_tcp_port(&address, timeout_ms).await,
+            ConnectionTarget::TcpRtu(address) => {
+                registry.add_tcp_rtu_port(&address, timeout_ms).await
+            }
+            ConnectionTarget::Simulator(url) => registry.add_simulator_port(&url).await,
+        };
+        match added {
+            Ok(_) => {
+                open_and_detect(&ui_weak, &port).await;
             }
             Err(e) => {
                 log::error!("串口连接失败: {}", e);
@@ -160,6 +271,162 @@ async fn handle_connect_click(ui_weak: Weak<AppWindow>, port: String) {
     }
 }
 
+// 打开已加入注册表的连接，成功后检测芯片并启动IO轮询
+async fn open_and_detect(ui_weak: &Weak<AppWindow>, port: &str) {
+    let registry = SerialPortRegistry::get_global().await;
+    update_ui_status(ui_weak, "连接中", "断开", true, false).await;
+
+    registry.open_all().await;
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+    if !registry.is_connected(port).await {
+        log::error!("连接失败: {}", port);
+        update_ui_status(ui_weak, "连接失败", "连接", false, false).await;
+        registry.remove_port(port).await;
+        return;
+    }
+
+    log::info!("连接成功: {}", port);
+    // 更新UI状态 - 已连接
+    update_ui_status(ui_weak, "已连接", "断开", true, false).await;
+
+    // 在界面上显示该连接的事务排队情况
+    if let Some(scheduler) = registry.get_scheduler(port).await {
+        watch_queue_depth(ui_weak.clone(), scheduler.subscribe_depth());
+    }
+
+    // 开始芯片检测
+    if let Some(port_manager) = registry
+        .get_scheduled_transport(port, Priority::Interactive)
+        .await
+    {
+        log::info!("开始检测芯片类型...");
+
+        let (chip1, chip2) = detect_all_chips(port_manager).await;
+
+        // 显示芯片系列和版本号，例如 "MALD rev 5"
+        let chip1_str = chip1.to_string();
+        let chip2_str = chip2.to_string();
+
+        // 更新芯片信息
+        update_chip_info(ui_weak, &chip1_str, &chip2_str).await;
+
+        log::info!("芯片检测完成: 芯片1={:?}, 芯片2={:?}", chip1, chip2);
+
+        // 未读取寄存器表时，载入芯片描述中的默认寄存器表
+        load_default_register_map(ui_weak, [&chip1.chip_type, &chip2.chip_type]).await;
+
+        // 启动IO状态轮询，IO 寄存器地址来自芯片描述
+        let chip1_io = chip1.chip_type.io_registers(CHIP1_IO_BASE);
+        let chip2_io = chip2.chip_type.io_registers(CHIP2_IO_BASE);
+        start_io_polling(ui_weak.clone(), port.to_string(), chip1_io, chip2_io).await;
+    }
+}
+
+// 连接的事务排队深度变化时更新界面，连接移除后清空
+fn watch_queue_depth(
+    ui_weak: Weak<AppWindow>,
+    mut depth: tokio::sync::watch::Receiver<QueueDepth>,
+) {
+    config::get_runtime().spawn(async move {
+        loop {
+            let current = *depth.borrow_and_update();
+            log::debug!("{}", current);
+            update_queue_status(&ui_weak, current.to_string());
+            if depth.changed().await.is_err() {
+                break;
+            }
+        }
+        update_queue_status(&ui_weak, String::new());
+    });
+}
+
+fn update_queue_status(ui_weak: &Weak<AppWindow>, status: String) {
+    let ui_weak_clone = ui_weak.clone();
+    let _ = slint::invoke_from_event_loop(move || {
+        if let Some(ui) = ui_weak_clone.upgrade() {
+            ui.global::<AppState>().set_queue_status(status.into());
+        }
+    });
+}
+
+// 寄存器表为空时，载入第一个带默认寄存器表的芯片的 CSV
+async fn load_default_register_map(ui_weak: &Weak<AppWindow>, chips: [&ChipType; 2]) {
+    let Some(register_map) = chips.iter().find_map(|chip| chip.register_map()) else {
+        return;
+    };
+    if !csv_handler::REGISTER_DATA.lock().await.is_empty() {
+        return;
+    }
+
+    match CsvHandler::load_csv_path(&register_map).await {
+        Ok(table_content) => {
+            log::info!("载入默认寄存器表: {:?}", register_map);
+            update_file_ui_success(ui_weak, table_content).await;
+        }
+        Err(e) => log::warn!("默认寄存器表 {:?} 载入失败: {}", register_map, e),
+    }
+}
+
+fn start_probe(ui_weak: Weak<AppWindow>) {
+    if let Some(ui) = ui_weak.upgrade() {
+        let state = ui.global::<AppState>();
+        let port = state.get_port_value().to_string();
+        let framing = Framing::from_name(&state.get_framing_value());
+        let settings = parse_serial_settings(&state);
+        log::info!("开始探测串口参数... {}", port);
+
+        config::get_runtime().spawn(async move {
+            handle_probe_click(ui_weak, port, settings, framing).await;
+        });
+    }
+}
+
+// 探测波特率和校验方式，成功后回填到连接面板并按该参数连接
+async fn handle_probe_click(
+    ui_weak: Weak<AppWindow>,
+    port: String,
+    settings: anyhow::Result<SerialSettings>,
+    framing: Framing,
+) {
+    let ConnectionTarget::Serial(path) = ConnectionTarget::parse(&port) else {
+        update_ui_status(&ui_weak, "仅串口可探测", "连接", false, false).await;
+        return;
+    };
+    let settings = match settings {
+        Ok(settings) => settings,
+        Err(e) => {
+            log::error!("串口参数无效: {}", e);
+            update_ui_status(&ui_weak, "参数无效", "连接", false, false).await;
+            return;
+        }
+    };
+
+    update_ui_status(&ui_weak, "探测中...", "连接", false, false).await;
+
+    let registry = SerialPortRegistry::get_global().await;
+    match registry.probe_port(&path, settings, framing).await {
+        Ok(found) => {
+            log::info!("串口 {} 探测到参数: {}", path, found);
+            let ui_weak_clone = ui_weak.clone();
+            let _ = slint::invoke_from_event_loop(move || {
+                if let Some(ui) = ui_weak_clone.upgrade() {
+                    ui.global::<AppState>()
+                        .set_baud_value(found.baud_rate.to_string().into());
+                    ui.global::<AppState>()
+                        .set_parity_value(found.parity_char().to_string().into());
+                }
+            });
+
+            open_and_detect(&ui_weak, &path).await;
+        }
+        Err(e) => {
+            log::error!("串口 {} 探测失败: {}", path, e);
+            update_ui_status(&ui_weak, "探测失败", "连接", false, false).await;
+        }
+    }
+}
+
 async fn update_ui_status(
     ui_weak: &Weak<AppWindow>,
     status: &str,
@@ -232,21 +499,25 @@ fn handle_io_chip_click(ui_weak: Weak<AppWindow>, chip_type: String, level: i32,
     config::get_runtime().spawn(async move {
         let registry = SerialPortRegistry::get_global().await;
 
-        if let Some(port_manager) = registry.get_port(&port).await {
+        if let Some(port_manager) = registry
+            .get_scheduled_transport(&port, Priority::Interactive)
+            .await
+        {
             let value = if level == 1 { 1u16 } else { 0u16 };
-            let slave_address = if address >= 0x4000 && address < 0x8000 {
+            let slave_address = if (0x4000..0x8000).contains(&address) {
                 1u8
             } else {
                 2u8
             };
 
             match write_single_register(port_manager, slave_address, address as u16, value).await {
-                Ok(()) => {
+                Ok(Retried { retries, .. }) => {
                     log::info!(
-                        "IO设置成功: 芯片={}, 地址=0x{:04X}, 值={}",
+                        "IO设置成功: 芯片={}, 地址=0x{:04X}, 值={}, 重试={}",
                         chip_type,
                         address,
-                        level
+                        level,
+                        retries
                     );
                 }
                 Err(e) => {
@@ -262,6 +533,78 @@ fn handle_io_chip_click(ui_weak: Weak<AppWindow>, chip_type: String, level: i32,
     });
 }
 
+// 处理总线扫描按钮点击事件
+fn handle_scan_bus_click(ui_weak: Weak<AppWindow>) {
+    let port = if let Some(ui) = ui_weak.upgrade() {
+        ui.global::<AppState>().get_port_value().to_string()
+    } else {
+        return;
+    };
+
+    config::get_runtime().spawn(async move {
+        let transport = match get_open_transport(&port, Priority::Bulk).await {
+            Ok(transport) => transport,
+            Err(e) => {
+                update_bus_scan_status(&ui_weak, format!("扫描失败: {}", e), false);
+                return;
+            }
+        };
+
+        update_bus_scan_results(&ui_weak, Vec::new());
+        let last_address = *SLAVE_ADDRESS_RANGE.end();
+        let results = scan_bus(
+            transport,
+            SLAVE_ADDRESS_RANGE,
+            SCAN_TIMEOUT_MS,
+            |address, found| {
+                update_bus_scan_status(
+                    &ui_weak,
+                    format!(
+                        "扫描中 {}/{}，已发现 {} 个从站",
+                        address, last_address, found
+                    ),
+                    true,
+                );
+            },
+        )
+        .await;
+
+        log::info!("总线扫描完成: 发现 {} 个从站", results.len());
+        update_bus_scan_status(
+            &ui_weak,
+            format!("扫描完成，发现 {} 个从站", results.len()),
+            false,
+        );
+        update_bus_scan_results(&ui_weak, results.iter().map(ToString::to_string).collect());
+    });
+}
+
+// 更新总线扫描状态
+fn update_bus_scan_status(ui_weak: &Weak<AppWindow>, status: String, is_scanning: bool) {
+    let ui_weak_clone = ui_weak.clone();
+    let _ = slint::invoke_from_event_loop(move || {
+        if let Some(ui) = ui_weak_clone.upgrade() {
+            ui.global::<AppState>().set_bus_scan_status(status.into());
+            ui.global::<AppState>().set_is_scanning(is_scanning);
+        }
+    });
+}
+
+// 更新总线扫描结果列表
+fn update_bus_scan_results(ui_weak: &Weak<AppWindow>, lines: Vec<String>) {
+    let ui_weak_clone = ui_weak.clone();
+    let _ = slint::invoke_from_event_loop(move || {
+        if let Some(ui) = ui_weak_clone.upgrade() {
+            let items: Vec<slint::StandardListViewItem> = lines
+                .iter()
+                .map(|line| slint::StandardListViewItem::from(line.as_str()))
+                .collect();
+            ui.global::<AppState>()
+                .set_bus_scan_results(slint::ModelRc::new(slint::VecModel::from(items)));
+        }
+    });
+}
+
 // 处理读取地址按钮点击事件
 fn handle_read_address_click(ui_weak: Weak<AppWindow>) {
     // 从UI中获取地址和端口信息
@@ -278,11 +621,16 @@ fn handle_read_address_click(ui_weak: Weak<AppWindow>) {
 
     config::get_runtime().spawn(async move {
         match read_address_operation(&ui_weak, address_str.clone(), port).await {
-            Ok(value) => {
+            Ok(Retried { value, retries }) => {
                 // 读取成功，在param-value中显示读取到的值，并更新文件状态
                 let ui_weak_clone = ui_weak.clone();
                 let value_str = format!("0x{:04X}", value);
-                let status_msg = format!("地址 {} 读取成功: {}", address_str, value_str);
+                let status_msg = format!(
+                    "地址 {} 读取成功: {}{}",
+                    address_str,
+                    value_str,
+                    retries_note(retries)
+                );
                 slint::invoke_from_event_loop(move || {
                     if let Some(ui) = ui_weak_clone.upgrade() {
                         ui.global::<AppState>().set_file_status(status_msg.into());
@@ -333,10 +681,15 @@ fn handle_write_address_click(ui_weak: Weak<AppWindow>) {
     config::get_runtime().spawn(async move {
         match write_address_operation(&ui_weak, address_str.clone(), value_str.clone(), port).await
         {
-            Ok(()) => {
+            Ok(Retried { retries, .. }) => {
                 // 写入成功，更新文件状态
                 let ui_weak_clone = ui_weak.clone();
-                let status_msg = format!("地址 {} 写入成功: {}", address_str, value_str);
+                let status_msg = format!(
+                    "地址 {} 写入成功: {}{}",
+                    address_str,
+                    value_str,
+                    retries_note(retries)
+                );
                 slint::invoke_from_event_loop(move || {
                     if let Some(ui) = ui_weak_clone.upgrade() {
                         ui.global::<AppState>().set_file_status(status_msg.into());
@@ -374,7 +727,7 @@ async fn read_address_operation(
     ui_weak: &Weak<AppWindow>,
     address_str: String,
     port: String,
-) -> Result<u16, String> {
+) -> Result<Retried<u16>, String> {
     // 验证地址格式是否为4位十六进制
     if address_str.len() != 4 {
         return Err("地址必须为4位十六进制字符串".to_string());
@@ -387,34 +740,44 @@ async fn read_address_operation(
     // 获取串口管理器
     let registry = SerialPortRegistry::get_global().await;
     let port_manager = registry
-        .get_port(&port)
+        .get_scheduled_transport(&port, Priority::Interactive)
         .await
         .ok_or("串口未连接".to_string())?;
 
     // 读取寄存器值
-    let value = read_single_register(port_manager, 1, address).await?;
+    let read = read_single_register(port_manager, 1, address).await?;
+    let value = read.value;
 
-    // 保存到全局HashMap中，key就是地址，结果存在w_value中
+    // 保存到全局寄存器表中，结果存在w_value中
     let address_key = format!("0x{:04X}", address);
-    let value_str = format!("0x{:02X}", value as u8);
 
     // 更新全局数据
     let mut global_data = crate::csv_handler::REGISTER_DATA.lock().await;
 
-    if let Some(existing_record) = global_data.get_mut(&address_key) {
-        // 如果key对应的记录已存在，只更新w_value
-        existing_record.w_value = Some(value_str.clone());
-        log::info!("更新现有记录: {} = {}", address_key, value_str);
+    if global_data.rows_at(address).next().is_some() {
+        // 如果该地址已有记录，只更新各行的w_value，按寄存器宽度显示
+        for existing_record in global_data.rows_at_mut(address) {
+            let value_str = existing_record.def.format_value(value as u32);
+            existing_record.w_value = Some(value_str.clone());
+            log::info!(
+                "更新现有记录: {}:{} = {}",
+                address_key,
+                existing_record.register,
+                value_str
+            );
+        }
     } else {
         // 如果key不存在，创建新的记录
-        let new_record = crate::csv_handler::RegisterRecord {
-            page_addr: address_key.clone(),
-            register: "".to_string(),
-            r_w: "R".to_string(),
-            value: "".to_string(),
-            w_value: Some(value_str.clone()),
-        };
-        global_data.insert(address_key.clone(), new_record);
+        let mut new_record = RegisterRecord::new(
+            address_key.clone(),
+            "".to_string(),
+            "R".to_string(),
+            "".to_string(),
+        );
+        let value_str = new_record.def.format_value(value as u32);
+        new_record.w_value = Some(value_str.clone());
+        new_record.status = RowStatus::Read;
+        global_data.insert(new_record);
         log::info!("创建新记录: {} = {}", address_key, value_str);
     }
 
@@ -425,7 +788,7 @@ async fn read_address_operation(
         .await
         .map_err(|e| e.to_string())?;
 
-    Ok(value)
+    Ok(read)
 }
 
 // 执行地址写入操作
@@ -434,7 +797,7 @@ async fn write_address_operation(
     address_str: String,
     value_str: String,
     port: String,
-) -> Result<(), String> {
+) -> Result<Retried<()>, String> {
     // 验证地址格式是否为4位十六进制
     if address_str.len() != 4 {
         return Err("地址必须为4位十六进制字符串".to_string());
@@ -456,38 +819,43 @@ async fn write_address_operation(
     // 获取串口管理器
     let registry = SerialPortRegistry::get_global().await;
     let port_manager = registry
-        .get_port(&port)
+        .get_scheduled_transport(&port, Priority::Interactive)
         .await
         .ok_or("串口未连接".to_string())?;
 
     // 写入寄存器值
-    write_single_register(port_manager, 1, address, value).await?;
+    let written = write_single_register(port_manager, 1, address, value).await?;
 
-    // 写入成功后，保存到全局HashMap中，key就是地址，结果存在value字段中
+    // 写入成功后，保存到全局寄存器表中，结果存在value字段中
     let address_key = format!("0x{:04X}", address);
-    let formatted_value_str = format!("0x{:02X}", value as u8);
 
     // 更新全局数据
     let mut global_data = crate::csv_handler::REGISTER_DATA.lock().await;
 
-    if let Some(existing_record) = global_data.get_mut(&address_key) {
-        // 如果key对应的记录已存在，只更新value字段
-        existing_record.value = formatted_value_str.clone();
-        log::info!(
-            "更新现有记录的value: {} = {}",
-            address_key,
-            formatted_value_str
-        );
+    if global_data.rows_at(address).next().is_some() {
+        // 如果该地址已有记录，只更新各行的value字段，按寄存器宽度显示
+        for existing_record in global_data.rows_at_mut(address) {
+            let formatted_value_str = existing_record.def.format_value(value as u32);
+            existing_record.value = formatted_value_str.clone();
+            log::info!(
+                "更新现有记录的value: {}:{} = {}",
+                address_key,
+                existing_record.register,
+                formatted_value_str
+            );
+        }
     } else {
         // 如果key不存在，创建新的记录
-        let new_record = crate::csv_handler::RegisterRecord {
-            page_addr: address_key.clone(),
-            register: "".to_string(),
-            r_w: "W".to_string(),
-            value: formatted_value_str.clone(),
-            w_value: None,
-        };
-        global_data.insert(address_key.clone(), new_record);
+        let mut new_record = RegisterRecord::new(
+            address_key.clone(),
+            "".to_string(),
+            "W".to_string(),
+            "".to_string(),
+        );
+        let formatted_value_str = new_record.def.format_value(value as u32);
+        new_record.value = formatted_value_str.clone();
+        new_record.status = RowStatus::Written;
+        global_data.insert(new_record);
         log::info!("创建新写入记录: {} = {}", address_key, formatted_value_str);
     }
 
@@ -498,87 +866,50 @@ async fn write_address_operation(
         .await
         .map_err(|e| e.to_string())?;
 
-    Ok(())
+    Ok(written)
 }
 
 // 启动IO状态轮询
-async fn start_io_polling(ui_weak: Weak<AppWindow>, port: String) {
+async fn start_io_polling(
+    ui_weak: Weak<AppWindow>,
+    port: String,
+    chip1_io: Vec<u16>,
+    chip2_io: Vec<u16>,
+) {
     let ui_weak_clone = ui_weak.clone();
 
     config::get_runtime().spawn(async move {
-        poll_io_status(ui_weak_clone, port).await;
+        poll_io_status(ui_weak_clone, port, chip1_io, chip2_io).await;
     });
 }
 
 // 轮询IO状态
-async fn poll_io_status(ui_weak: Weak<AppWindow>, port: String) {
+async fn poll_io_status(
+    ui_weak: Weak<AppWindow>,
+    port: String,
+    chip1_io: Vec<u16>,
+    chip2_io: Vec<u16>,
+) {
     let registry = SerialPortRegistry::get_global().await;
     log::info!("开始轮询IO状态: {}", port);
 
     loop {
-        if let Some(port_manager) = registry.get_port(&port).await {
-            // 逐个读取芯片一的IO状态
-            let mut chip1_values = Vec::new();
-
-            // 读取芯片一 IO1 (0x4001)
-            match read_single_register(port_manager.clone(), 1, 0x4001).await {
-                Ok(value) => chip1_values.push((value & 1) as i32),
-                Err(e) => {
-                    log::error!("读取芯片一IO1失败: {}", e);
-                    chip1_values.push(0);
-                }
-            }
-
-            // 读取芯片一 IO2 (0x4002)
-            match read_single_register(port_manager.clone(), 1, 0x4002).await {
-                Ok(value) => chip1_values.push((value & 1) as i32),
-                Err(e) => {
-                    log::error!("读取芯片一IO2失败: {}", e);
-                    chip1_values.push(0);
-                }
-            }
-
-            // 读取芯片一 IO3 (0x4003)
-            match read_single_register(port_manager.clone(), 1, 0x4003).await {
-                Ok(value) => chip1_values.push((value & 1) as i32),
-                Err(e) => {
-                    log::error!("读取芯片一IO3失败: {}", e);
-                    chip1_values.push(0);
-                }
-            }
-
-            // 逐个读取芯片二的IO状态
-            let mut chip2_values = Vec::new();
-
-            // 读取芯片二 IO1 (0xC001)
-            match read_single_register(port_manager.clone(), 1, 0xC001).await {
-                Ok(value) => chip2_values.push((value & 1) as i32),
-                Err(e) => {
-                    log::error!("读取芯片二IO1失败: {}", e);
-                    chip2_values.push(0);
-                }
-            }
-
-            // 读取芯片二 IO2 (0xC002)
-            match read_single_register(port_manager.clone(), 1, 0xC002).await {
-                Ok(value) => chip2_values.push((value & 1) as i32),
-                Err(e) => {
-                    log::error!("读取芯片二IO2失败: {}", e);
-                    chip2_values.push(0);
-                }
-            }
+        if let Some(port_manager) = registry
+            .get_scheduled_transport(&port, Priority::Polling)
+            .await
+        {
+            // 读取芯片一的 IO1-IO3 (默认 0x4001-0x4003)
+            let chip1_values = device_io::read_io_status(port_manager.clone(), &chip1_io)
+                .await
+                .map_err(|e| e.to_string());
 
-            // 读取芯片二 IO3 (0xC003)
-            match read_single_register(port_manager.clone(), 1, 0xC003).await {
-                Ok(value) => chip2_values.push((value & 1) as i32),
-                Err(e) => {
-                    log::error!("读取芯片二IO3失败: {}", e);
-                    chip2_values.push(0);
-                }
-            }
+            // 读取芯片二的 IO1-IO3 (默认 0xC001-0xC003)
+            let chip2_values = device_io::read_io_status(port_manager, &chip2_io)
+                .await
+                .map_err(|e| e.to_string());
 
             // 更新UI状态
-            update_io_status(&ui_weak, Ok(chip1_values), Ok(chip2_values)).await;
+            update_io_status(&ui_weak, chip1_values, chip2_values).await;
 
             tokio::time::sleep(Duration::from_millis(2000)).await;
         } else {
@@ -588,70 +919,63 @@ async fn poll_io_status(ui_weak: Weak<AppWindow>, port: String) {
     }
 }
 
+// 生成面向操作员的错误说明，从站异常响应显示异常码的含义
+fn describe_modbus_error(error: &anyhow::Error) -> String {
+    match error
+        .downcast_ref::<ModbusError>()
+        .and_then(ModbusError::exception)
+    {
+        Some(exception) => format!("从站返回异常 - {}", exception),
+        // 重试后失败时包含重试次数，例如 "已重试 2 次: Response timeout"
+        None => format!("{:#}", error),
+    }
+}
+
+// 状态栏中附加的重试次数说明
+fn retries_note(retries: u32) -> String {
+    if retries > 0 {
+        format!(" (重试 {} 次)", retries)
+    } else {
+        String::new()
+    }
+}
+
 // 通用读单个寄存器方法
 async fn read_single_register(
-    port_manager: std::sync::Arc<crate::serial::base::SerialPortManager>,
+    port_manager: Arc<dyn ModbusTransport>,
     slave_address: u8,
     register_address: u16,
-) -> Result<u16, String> {
-    let command = match ModbusFrame::new_read_request(
-        slave_address,
-        RegisterType::HoldingRegister,
-        register_address,
-        1, // 读取1个寄存器
-    ) {
-        Ok(cmd) => cmd,
-        Err(e) => return Err(format!("创建读命令失败: {}", e)),
-    };
-
-    match port_manager
-        .send_modbus_command(&command.to_bytes(), 1000)
-        .await
+) -> Result<Retried<u16>, String> {
+    let policy = SerialPortRegistry::get_global().await.retry_policy();
+    match with_retry(&policy, || {
+        port_manager.read_registers(slave_address, register_address, 1)
+    })
+    .await
     {
-        Ok(response) => {
-            match ModbusFrame::from_bytes(&response) {
-                Ok(frame) => {
-                    let data = frame.get_data();
-                    if data.len() >= 3 {
-                        // 数据格式: [字节数, 高字节, 低字节]
-                        let word = ((data[1] as u16) << 8) | (data[2] as u16);
-                        Ok(word)
-                    } else {
-                        Err("响应数据长度不足".to_string())
-                    }
-                }
-                Err(e) => Err(format!("解析响应失败: {}", e)),
-            }
-        }
-        Err(e) => Err(format!("发送命令失败: {}", e)),
+        Ok(read) => match read.value.first() {
+            Some(&value) => Ok(Retried {
+                value,
+                retries: read.retries,
+            }),
+            None => Err("响应数据长度不足".to_string()),
+        },
+        Err(e) => Err(format!("读取寄存器失败: {}", describe_modbus_error(&e))),
     }
 }
 
 // 通用写单个寄存器方法
 async fn write_single_register(
-    port_manager: std::sync::Arc<crate::serial::base::SerialPortManager>,
+    port_manager: Arc<dyn ModbusTransport>,
     slave_address: u8,
     register_address: u16,
     value: u16,
-) -> Result<(), String> {
-    let mut data = Vec::new();
-    data.push((register_address >> 8) as u8); // 地址高字节
-    data.push(register_address as u8); // 地址低字节
-    data.push((value >> 8) as u8); // 值高字节
-    data.push(value as u8); // 值低字节
-
-    let command = ModbusFrame::new(slave_address, 0x06, data); // 功能码 0x06 写单个保持寄存器
-
-    match port_manager
-        .send_modbus_command(&command.to_bytes(), 1000)
-        .await
-    {
-        Ok(response) => match ModbusFrame::from_bytes(&response) {
-            Ok(_frame) => Ok(()),
-            Err(e) => Err(format!("解析写响应失败: {}", e)),
-        },
-        Err(e) => Err(format!("发送写命令失败: {}", e)),
-    }
+) -> Result<Retried<()>, String> {
+    let policy = SerialPortRegistry::get_global().await.retry_policy();
+    with_retry(&policy, || {
+        port_manager.write_register(slave_address, register_address, value)
+    })
+    .await
+    .map_err(|e| format!("写寄存器失败: {}", describe_modbus_error(&e)))
 }
 
 // 更新IO状态到UI
@@ -714,25 +1038,224 @@ fn handle_read_file_click(ui_weak: Weak<AppWindow>) {
     });
 }
 
-// 更新文件操作UI状态 - 成功
-async fn update_file_ui_success(ui_weak: &Weak<AppWindow>, content: String) {
-    let ui_weak_clone = ui_weak.clone();
-    let content_clone = content.clone();
-
-    // 获取表格数据
-    let table_data = match CsvHandler::get_slint_table_data().await {
-        Ok(data) => data,
-        Err(e) => {
-            log::error!("获取表格数据失败: {}", e);
-            vec![]
-        }
+// 处理导出按钮点击事件，把当前寄存器表和读回的值保存为带时间戳的快照文件
+fn handle_export_click(ui_weak: Weak<AppWindow>) {
+    // 提前获取串口和芯片信息，避免在异步任务中访问UI
+    let (port, chip) = if let Some(ui) = ui_weak.upgrade() {
+        let state = ui.global::<AppState>();
+        let chips: Vec<String> = [state.get_chip1_type(), state.get_chip2_type()]
+            .into_iter()
+            .filter(|chip| !chip.is_empty())
+            .map(|chip| chip.to_string())
+            .collect();
+        (state.get_port_value().to_string(), chips.join(" / "))
+    } else {
+        return;
     };
 
-    slint::invoke_from_event_loop(move || {
-        if let Some(ui) = ui_weak_clone.upgrade() {
-            // 更新文件状态
-            ui.global::<AppState>()
-                .set_file_status("文件读取成功".into());
+    config::get_runtime().spawn(async move {
+        match export_snapshot(&port, &chip).await {
+            Ok(Some(status)) => update_file_status(
+                &ui_weak,
+                status,
+                slint::Color::from_rgb_u8(40, 167, 69), // 绿色
+            ),
+            Ok(None) => {}
+            Err(e) => {
+                log::error!("导出失败: {}", e);
+                update_file_status(
+                    &ui_weak,
+                    format!("导出失败: {}", e),
+                    slint::Color::from_rgb_u8(220, 53, 69), // 红色
+                );
+            }
+        }
+    });
+}
+
+// 选择文件并导出快照，返回状态栏文本；未选择文件时返回 None
+async fn export_snapshot(port: &str, chip: &str) -> anyhow::Result<Option<String>> {
+    let records = CsvHandler::get_all_records().await?;
+    if records.is_empty() {
+        return Err(anyhow::anyhow!("没有寄存器数据，请先读取文件"));
+    }
+
+    let snapshot = Snapshot::capture(&records, port, chip);
+    let Some(file_path) = export::select_export_file("导出寄存器快照", &snapshot.file_name("csv"))
+    else {
+        return Ok(None);
+    };
+    snapshot.save(&file_path)?;
+
+    let read_count = records
+        .iter()
+        .filter(|record| record.w_value.is_some())
+        .count();
+    log::info!("导出快照 {:?}: {} 条", file_path, records.len());
+    Ok(Some(format!(
+        "已导出 {} 条寄存器 (已读取 {} 条) 到 {}",
+        records.len(),
+        read_count,
+        file_path.display()
+    )))
+}
+
+// 处理快照比较按钮点击事件
+// 选择一个快照时与寄存器表的设置值比较，选择两个快照时以第一个为基准比较
+fn handle_compare_click(ui_weak: Weak<AppWindow>) {
+    let use_masks = if let Some(ui) = ui_weak.upgrade() {
+        ui.global::<AppState>().get_compare_use_masks()
+    } else {
+        return;
+    };
+
+    config::get_runtime().spawn(async move {
+        let diff = match compare_snapshots(use_masks).await {
+            Ok(Some(diff)) => diff,
+            Ok(None) => return,
+            Err(e) => {
+                log::error!("快照比较失败: {}", e);
+                update_file_status(
+                    &ui_weak,
+                    format!("快照比较失败: {}", e),
+                    slint::Color::from_rgb_u8(220, 53, 69), // 红色
+                );
+                return;
+            }
+        };
+
+        log::info!("快照比较 {} / {}: {}", diff.base, diff.other, diff.summary);
+        let color = if diff.summary.is_identical() {
+            slint::Color::from_rgb_u8(40, 167, 69) // 绿色
+        } else {
+            slint::Color::from_rgb_u8(255, 193, 7) // 橙色
+        };
+        update_file_status(
+            &ui_weak,
+            format!("{} 与 {}: {}", diff.base, diff.other, diff.summary),
+            color,
+        );
+        show_compare_result(&ui_weak, &diff);
+        *LAST_COMPARE.lock().await = Some(diff);
+    });
+}
+
+// 选择快照文件并比较，未选择文件时返回 None
+async fn compare_snapshots(use_masks: bool) -> anyhow::Result<Option<SnapshotDiff>> {
+    let Some(files) = export::select_snapshot_files() else {
+        return Ok(None);
+    };
+
+    let (base, other) = match files.as_slice() {
+        [dump] => {
+            let records = CsvHandler::get_all_records().await?;
+            if records.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "没有寄存器数据，请先读取文件或选择两个快照"
+                ));
+            }
+            (
+                CompareSource::from_golden("设置值", &records)?,
+                CompareSource::load(dump)?,
+            )
+        }
+        [base, other] => (CompareSource::load(base)?, CompareSource::load(other)?),
+        _ => return Err(anyhow::anyhow!("请选择一个或两个快照文件")),
+    };
+
+    Ok(Some(snapshot_compare::compare(&base, &other, use_masks)))
+}
+
+// 在表格中显示比较结果: 页地址、寄存器、差异、基准值、比较值、掩码
+fn show_compare_result(ui_weak: &Weak<AppWindow>, diff: &SnapshotDiff) {
+    let rows: Vec<Vec<slint::SharedString>> = diff
+        .entries
+        .iter()
+        .map(|entry| {
+            vec![
+                entry.page_addr.clone().into(),
+                entry.register.clone().into(),
+                entry.kind.to_string().into(),
+                entry.base_value.clone().into(),
+                entry.other_value.clone().into(),
+                entry.mask.clone().into(),
+            ]
+        })
+        .collect();
+    let summary = diff.summary.to_string();
+
+    let ui_weak_clone = ui_weak.clone();
+    let _ = slint::invoke_from_event_loop(move || {
+        if let Some(ui) = ui_weak_clone.upgrade() {
+            ui.global::<AppState>()
+                .set_compare_table_data(to_table_model(rows));
+            ui.global::<AppState>().set_compare_summary(summary.into());
+            ui.global::<AppState>().set_compare_visible(true);
+        }
+    });
+}
+
+// 处理导出比较报告按钮点击事件
+fn handle_export_report_click(ui_weak: Weak<AppWindow>) {
+    config::get_runtime().spawn(async move {
+        let Some(diff) = LAST_COMPARE.lock().await.clone() else {
+            return;
+        };
+        let Some(file_path) = export::select_export_file("导出比较报告", &diff.report_file_name())
+        else {
+            return;
+        };
+
+        match diff.save_report(&file_path) {
+            Ok(()) => {
+                log::info!("导出比较报告 {:?}", file_path);
+                update_file_status(
+                    &ui_weak,
+                    format!("已导出比较报告到 {}", file_path.display()),
+                    slint::Color::from_rgb_u8(40, 167, 69), // 绿色
+                );
+            }
+            Err(e) => {
+                log::error!("导出比较报告失败: {}", e);
+                update_file_status(
+                    &ui_weak,
+                    format!("导出比较报告失败: {}", e),
+                    slint::Color::from_rgb_u8(220, 53, 69), // 红色
+                );
+            }
+        }
+    });
+}
+
+// 处理关闭比较结果按钮点击事件，表格恢复显示寄存器表
+fn handle_close_compare_click(ui_weak: Weak<AppWindow>) {
+    config::get_runtime().spawn(async move {
+        *LAST_COMPARE.lock().await = None;
+    });
+    if let Some(ui) = ui_weak.upgrade() {
+        ui.global::<AppState>().set_compare_visible(false);
+    }
+}
+
+// 更新文件操作UI状态 - 成功
+async fn update_file_ui_success(ui_weak: &Weak<AppWindow>, content: String) {
+    let ui_weak_clone = ui_weak.clone();
+    let content_clone = content.clone();
+
+    // 获取表格数据
+    let table_data = match CsvHandler::get_slint_table_data().await {
+        Ok(data) => data,
+        Err(e) => {
+            log::error!("获取表格数据失败: {}", e);
+            vec![]
+        }
+    };
+
+    slint::invoke_from_event_loop(move || {
+        if let Some(ui) = ui_weak_clone.upgrade() {
+            // 更新文件状态
+            ui.global::<AppState>()
+                .set_file_status("文件读取成功".into());
             ui.global::<AppState>()
                 .set_file_status_color(slint::Brush::from(slint::Color::from_rgb_u8(40, 167, 69))); // 绿色
 
@@ -747,7 +1270,7 @@ async fn update_file_ui_success(ui_weak: &Weak<AppWindow>, content: String) {
                     .map(|row| {
                         let row_model = slint::VecModel::from(
                             row.into_iter()
-                                .map(|cell| slint::StandardListViewItem::from(cell))
+                                .map(slint::StandardListViewItem::from)
                                 .collect::<Vec<_>>(),
                         );
                         slint::ModelRc::new(row_model)
@@ -801,12 +1324,27 @@ fn handle_read_device_click(ui_weak: Weak<AppWindow>) {
 
     // 在后台线程中执行器件读取操作
     config::get_runtime().spawn(async move {
+        let job = match Job::start(JobKind::ReadDevice) {
+            Ok(job) => job,
+            Err(e) => {
+                update_device_read_ui_error(&ui_weak_clone, e.to_string()).await;
+                return;
+            }
+        };
+        set_job_running(&ui_weak_clone, true);
+
         // 执行器件读取操作
-        match read_device_registers(&ui_weak_clone, &port_path).await {
-            Ok(_) => {
-                log::info!("器件读取完成");
+        let result = read_device_registers(&ui_weak_clone, &port_path, &job).await;
+        set_job_running(&ui_weak_clone, false);
+        match result {
+            Ok(retries) => {
+                log::info!("器件读取完成，重试 {} 次", retries);
                 // 更新UI状态为成功
-                update_device_read_ui_success(&ui_weak_clone).await;
+                update_device_read_ui_success(&ui_weak_clone, retries).await;
+            }
+            Err(e) if job.is_cancelled() => {
+                log::warn!("器件读取{}", e);
+                update_job_stopped_ui(&ui_weak_clone, JobKind::ReadDevice, e.to_string());
             }
             Err(e) => {
                 log::error!("器件读取失败: {}", e);
@@ -818,90 +1356,137 @@ fn handle_read_device_click(ui_weak: Weak<AppWindow>) {
 }
 
 // 执行器件寄存器读取操作
-async fn read_device_registers(ui_weak: &Weak<AppWindow>, port_path: &str) -> anyhow::Result<()> {
-    use crate::csv_handler::CsvHandler;
-    use crate::serial::manager::SerialPortRegistry;
-
-    // 获取串口注册表
-    let registry = SerialPortRegistry::get_global().await;
+// 读完的块立即记入寄存器表，停止或出错时表格中未读到的行标记为未完成；
+// 返回读取过程中合计的重试次数
+async fn read_device_registers(
+    ui_weak: &Weak<AppWindow>,
+    port_path: &str,
+    job: &Job,
+) -> anyhow::Result<u32> {
+    let port_manager = get_open_transport(port_path, Priority::Bulk).await?;
     let records = CsvHandler::get_all_records().await?;
+    let policy = SerialPortRegistry::get_global().await.retry_policy();
 
-    // 获取当前连接的串口
-    let port_manager = if let Some(manager) = registry.get_port(port_path).await {
-        if manager.is_open() {
-            manager
-        } else {
-            return Err(anyhow::anyhow!("串口 {} 未连接", port_path));
-        }
-    } else {
-        return Err(anyhow::anyhow!("串口 {} 不存在", port_path));
-    };
+    let readable: Vec<RegisterRecord> = records
+        .iter()
+        .filter(|record| device_io::is_readable(record))
+        .cloned()
+        .collect();
+    CsvHandler::set_status(&readable, RowStatus::Pending).await;
+    update_table_data_after_read(ui_weak).await?;
 
-    let total_pages = records.len();
-    let mut processed = 0;
+    let (block_sender, blocks) = tokio::sync::mpsc::unbounded_channel();
+    let read = device_io::read_device_registers(
+        port_manager,
+        &records,
+        &policy,
+        job.cancel_token(),
+        |index, total, block_label| update_read_progress_status(ui_weak, index, total, block_label),
+        move |block| {
+            let _ = block_sender.send(block.to_vec());
+        },
+    );
+    // 更新已读完的寄存器的w_value
+    let applied = apply_completed_blocks(ui_weak, blocks, |block| async move {
+        for record in &block {
+            CsvHandler::update_w_value(&record.page_addr, &record.register, record.w_value.clone())
+                .await
+                .map_err(|e| anyhow::anyhow!("更新寄存器值失败: {}", e))?;
+        }
+        CsvHandler::set_status(&block, RowStatus::Read).await;
+        Ok(())
+    });
+    let (read, applied) = tokio::join!(read, applied);
 
-    // 获取该页的所有寄存器记录
+    CsvHandler::mark_pending_unfinished().await;
+    // 完成后更新表格数据
+    update_table_data_after_read(ui_weak).await?;
 
-    for record in records {
-        let page_addr = record.page_addr.clone();
+    applied?;
+    Ok(read?.retries)
+}
 
-        update_read_progress_status(ui_weak, processed, total_pages, &page_addr).await;
-        // 只读取标记为可读的寄存器
-        if record.r_w.to_uppercase().contains('R') {
-            // 解析页地址（假设是十六进制格式）
-            let page_addr_u16 = if page_addr.starts_with("0x") || page_addr.starts_with("0X") {
-                u16::from_str_radix(&page_addr[2..], 16)
-                    .map_err(|_| anyhow::anyhow!("无效的页地址格式: {}", page_addr))?
-            } else {
-                page_addr
-                    .parse::<u16>()
-                    .map_err(|_| anyhow::anyhow!("无效的页地址: {}", page_addr))?
-            };
+// 任务每完成一块，立即按 apply 记入寄存器表并刷新表格；
+// 任务结束 (发送端释放) 后返回全部已完成的项
+async fn apply_completed_blocks<T, F, Fut>(
+    ui_weak: &Weak<AppWindow>,
+    mut blocks: tokio::sync::mpsc::UnboundedReceiver<Vec<T>>,
+    mut apply: F,
+) -> anyhow::Result<Vec<T>>
+where
+    T: Clone,
+    F: FnMut(Vec<T>) -> Fut,
+    Fut: std::future::Future<Output = anyhow::Result<()>>,
+{
+    let mut finished = Vec::new();
+    while let Some(block) = blocks.recv().await {
+        if let Err(e) = apply(block.clone()).await {
+            log::warn!("{}", e);
+            return Err(e);
+        }
+        update_table_data_after_read(ui_weak).await?;
+        finished.extend(block);
+    }
+    Ok(finished)
+}
 
-            match read_single_register(port_manager.clone(), 1, page_addr_u16).await {
-                Ok(value) => {
-                    // 更新寄存器的w_value
-                    let hex_value = format!("0x{:02X}", value as u8);
+// 处理停止按钮点击事件
+fn handle_stop_job_click(ui_weak: Weak<AppWindow>) {
+    let Some(kind) = job::cancel_active_job() else {
+        return;
+    };
 
-                    log::info!(
-                        "读取寄存器成功: {}:{} = {}",
-                        page_addr,
-                        record.register,
-                        hex_value
-                    );
-                    if let Err(e) =
-                        CsvHandler::update_w_value(&page_addr, &record.register, Some(hex_value))
-                            .await
-                    {
-                        log::warn!("更新寄存器值失败: {}", e);
-                        return Err(anyhow::anyhow!("更新寄存器值失败: {}", e));
-                    }
-                }
-                Err(e) => {
-                    log::warn!("读取寄存器失败 {}:{} - {}", page_addr, record.register, e);
-                    return Err(anyhow::anyhow!(
-                        "读取寄存器失败 {}:{} - {}",
-                        page_addr,
-                        record.register,
-                        e
-                    ));
-                }
-            }
+    let status_text = format!("正在停止{}，等待当前块完成...", kind);
+    slint::invoke_from_event_loop(move || {
+        if let Some(ui) = ui_weak.upgrade() {
+            ui.global::<AppState>().set_file_status(status_text.into());
+            ui.global::<AppState>()
+                .set_file_status_color(slint::Brush::from(slint::Color::from_rgb_u8(255, 193, 7))); // 橙色
+        }
+    })
+    .unwrap();
+}
 
-            // 添加小延时避免过于频繁的通信
-            tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
-            processed += 1;
+// 更新器件读写任务的运行状态，控制停止按钮是否可用
+fn set_job_running(ui_weak: &Weak<AppWindow>, running: bool) {
+    let ui_weak_clone = ui_weak.clone();
+    let _ = slint::invoke_from_event_loop(move || {
+        if let Some(ui) = ui_weak_clone.upgrade() {
+            ui.global::<AppState>().set_job_running(running);
         }
-    }
+    });
+}
 
-    // 完成后更新表格数据
-    update_table_data_after_read(ui_weak).await?;
+// 更新任务被停止后的UI状态，message 为已完成的进度
+fn update_job_stopped_ui(ui_weak: &Weak<AppWindow>, kind: JobKind, message: String) {
+    let ui_weak_clone = ui_weak.clone();
+    let status_text = format!("{}{}", kind, message);
 
-    Ok(())
+    slint::invoke_from_event_loop(move || {
+        if let Some(ui) = ui_weak_clone.upgrade() {
+            ui.global::<AppState>().set_file_status(status_text.into());
+            ui.global::<AppState>()
+                .set_file_status_color(slint::Brush::from(slint::Color::from_rgb_u8(255, 193, 7))); // 橙色
+        }
+    })
+    .unwrap();
+}
+
+// 以指定优先级获取已打开的连接
+async fn get_open_transport(
+    port_path: &str,
+    priority: Priority,
+) -> anyhow::Result<Arc<dyn ModbusTransport>> {
+    let registry = SerialPortRegistry::get_global().await;
+    match registry.get_scheduled_transport(port_path, priority).await {
+        Some(manager) if manager.is_open() => Ok(manager),
+        Some(_) => Err(anyhow::anyhow!("串口 {} 未连接", port_path)),
+        None => Err(anyhow::anyhow!("串口 {} 不存在", port_path)),
+    }
 }
 
 // 更新读取进度状态
-async fn update_read_progress_status(
+fn update_read_progress_status(
     ui_weak: &Weak<AppWindow>,
     processed: usize,
     total: usize,
@@ -940,7 +1525,7 @@ async fn update_table_data_after_read(ui_weak: &Weak<AppWindow>) -> anyhow::Resu
                     .map(|row| {
                         let row_model = slint::VecModel::from(
                             row.into_iter()
-                                .map(|cell| slint::StandardListViewItem::from(cell))
+                                .map(slint::StandardListViewItem::from)
                                 .collect::<Vec<_>>(),
                         );
                         slint::ModelRc::new(row_model)
@@ -957,13 +1542,13 @@ async fn update_table_data_after_read(ui_weak: &Weak<AppWindow>) -> anyhow::Resu
 }
 
 // 更新器件读取UI状态 - 成功
-async fn update_device_read_ui_success(ui_weak: &Weak<AppWindow>) {
+async fn update_device_read_ui_success(ui_weak: &Weak<AppWindow>, retries: u32) {
     let ui_weak_clone = ui_weak.clone();
+    let status_text = format!("器件读取完成{}", retries_note(retries));
 
     slint::invoke_from_event_loop(move || {
         if let Some(ui) = ui_weak_clone.upgrade() {
-            ui.global::<AppState>()
-                .set_file_status("器件读取完成".into());
+            ui.global::<AppState>().set_file_status(status_text.into());
             ui.global::<AppState>()
                 .set_file_status_color(slint::Brush::from(slint::Color::from_rgb_u8(40, 167, 69))); // 绿色
         }
@@ -987,10 +1572,15 @@ async fn update_device_read_ui_error(ui_weak: &Weak<AppWindow>, error_msg: Strin
 }
 
 // 处理写入器件按钮点击事件
+// 开启写入前预览时先读取当前值并显示差异，等待确认；否则直接写入全部可写寄存器
 fn handle_write_device_click(ui_weak: Weak<AppWindow>) {
     // 提前获取串口路径，避免在异步任务中访问UI
-    let port_path = if let Some(ui) = ui_weak.upgrade() {
-        ui.global::<AppState>().get_port_value().to_string()
+    let (port_path, verify, preview) = if let Some(ui) = ui_weak.upgrade() {
+        (
+            ui.global::<AppState>().get_port_value().to_string(),
+            ui.global::<AppState>().get_verify_after_write(),
+            ui.global::<AppState>().get_preview_before_write(),
+        )
     } else {
         log::error!("UI界面已关闭");
         return;
@@ -1000,139 +1590,404 @@ fn handle_write_device_click(ui_weak: Weak<AppWindow>) {
 
     // 在后台线程中执行器件写入操作
     config::get_runtime().spawn(async move {
-        // 执行器件写入操作
-        match write_device_registers(&ui_weak_clone, &port_path).await {
-            Ok(_) => {
-                log::info!("器件写入完成");
-                // 更新UI状态为成功
-                update_device_write_ui_success(&ui_weak_clone).await;
-            }
-            Err(e) => {
-                log::error!("器件写入失败: {}", e);
-                // 更新UI错误状态
-                update_device_write_ui_error(&ui_weak_clone, e.to_string()).await;
-            }
+        if preview {
+            run_write_preview(ui_weak_clone, port_path).await;
+            return;
+        }
+
+        match CsvHandler::get_all_records().await {
+            Ok(records) => run_device_write(ui_weak_clone, port_path, records, verify).await,
+            Err(e) => update_device_write_ui_error(&ui_weak_clone, e.to_string()).await,
         }
     });
 }
 
-// 执行器件寄存器写入操作
-async fn write_device_registers(ui_weak: &Weak<AppWindow>, port_path: &str) -> anyhow::Result<()> {
-    use crate::csv_handler::CsvHandler;
-    use crate::serial::manager::SerialPortRegistry;
+// 作为可停止的任务写入 records 中的可写寄存器，并更新UI状态
+async fn run_device_write(
+    ui_weak: Weak<AppWindow>,
+    port_path: String,
+    records: Vec<RegisterRecord>,
+    verify: bool,
+) {
+    let job = match Job::start(JobKind::WriteDevice) {
+        Ok(job) => job,
+        Err(e) => {
+            update_device_write_ui_error(&ui_weak, e.to_string()).await;
+            return;
+        }
+    };
+    set_job_running(&ui_weak, true);
+
+    // 执行器件写入操作
+    let result = write_device_registers(&ui_weak, &port_path, &job, &records, verify).await;
+    set_job_running(&ui_weak, false);
+    match result {
+        Ok((retries, summary)) => {
+            match &summary {
+                Some(summary) => log::info!("器件写入完成，{}，重试 {} 次", summary, retries),
+                None => log::info!("器件写入完成，重试 {} 次", retries),
+            }
+            // 更新UI状态为成功
+            update_device_write_ui_success(&ui_weak, retries, summary).await;
+        }
+        Err(e) if job.is_cancelled() => {
+            log::warn!("器件写入{}", e);
+            update_job_stopped_ui(&ui_weak, JobKind::WriteDevice, e.to_string());
+        }
+        Err(e) => {
+            log::error!("器件写入失败: {}", e);
+            // 更新UI错误状态
+            update_device_write_ui_error(&ui_weak, e.to_string()).await;
+        }
+    }
+}
+
+// 读取可写寄存器的当前值并显示差异，有需要修改的寄存器时等待用户确认
+async fn run_write_preview(ui_weak: Weak<AppWindow>, port_path: String) {
+    let job = match Job::start(JobKind::PreviewWrite) {
+        Ok(job) => job,
+        Err(e) => {
+            update_device_write_ui_error(&ui_weak, e.to_string()).await;
+            return;
+        }
+    };
+    set_job_running(&ui_weak, true);
+
+    let result = preview_device_write(&ui_weak, &port_path, &job).await;
+    set_job_running(&ui_weak, false);
+    let diffs = match result {
+        Ok(diffs) => diffs,
+        Err(e) if job.is_cancelled() => {
+            log::warn!("写入预览{}", e);
+            update_job_stopped_ui(&ui_weak, JobKind::PreviewWrite, e.to_string());
+            return;
+        }
+        Err(e) => {
+            log::error!("写入预览失败: {}", e);
+            update_device_write_ui_error(&ui_weak, e.to_string()).await;
+            return;
+        }
+    };
+
+    let changed = diffs.iter().filter(|diff| diff.will_change()).count();
+    let unchanged = diffs.len() - changed;
+    log::info!("写入预览: {} 个寄存器将修改，{} 个不变", changed, unchanged);
+
+    if changed == 0 {
+        *PENDING_WRITE.lock().await = None;
+        update_file_status(
+            &ui_weak,
+            format!("{} 个可写寄存器与器件当前值一致，无需写入", unchanged),
+            slint::Color::from_rgb_u8(40, 167, 69), // 绿色
+        );
+        return;
+    }
 
-    // 获取所有寄存器记录
+    let summary = format!("{} 个寄存器将修改，{} 个不变", changed, unchanged);
+    show_write_diff(&ui_weak, &diffs, summary.clone());
+    *PENDING_WRITE.lock().await = Some(PendingWrite { port_path, diffs });
+    update_file_status(
+        &ui_weak,
+        format!("写入预览: {}，确认后写入", summary),
+        slint::Color::from_rgb_u8(23, 162, 184), // 蓝色
+    );
+}
+
+// 读取可写寄存器的当前值，返回与设置值的差异
+async fn preview_device_write(
+    ui_weak: &Weak<AppWindow>,
+    port_path: &str,
+    job: &Job,
+) -> anyhow::Result<Vec<WriteDiff>> {
     let all_records = CsvHandler::get_all_records().await?;
     if all_records.is_empty() {
         return Err(anyhow::anyhow!("没有找到寄存器数据，请先读取文件"));
     }
 
-    // 过滤只有RW（可读写）的记录
-    let writable_records: Vec<_> = all_records
-        .into_iter()
-        .filter(|record| {
-            record.r_w.to_uppercase().contains("RW") || record.r_w.to_uppercase() == "W"
-        })
-        .collect();
+    let port_manager = get_open_transport(port_path, Priority::Bulk).await?;
+    let policy = SerialPortRegistry::get_global().await.retry_policy();
 
-    if writable_records.is_empty() {
-        return Err(anyhow::anyhow!("没有找到可写入的寄存器"));
-    }
+    let preview = device_io::preview_device_write(
+        port_manager,
+        &all_records,
+        &policy,
+        job.cancel_token(),
+        |index, total, block_label| update_read_progress_status(ui_weak, index, total, block_label),
+    )
+    .await?;
 
-    // 获取串口注册表
-    let registry = SerialPortRegistry::get_global().await;
+    Ok(preview.value)
+}
 
-    // 获取当前连接的串口
-    let port_manager = if let Some(manager) = registry.get_port(port_path).await {
-        if manager.is_open() {
-            manager
-        } else {
-            return Err(anyhow::anyhow!("串口 {} 未连接", port_path));
-        }
+// 处理确认写入按钮点击事件，只写入预览中值有变化的寄存器
+fn handle_confirm_write_click(ui_weak: Weak<AppWindow>) {
+    let verify = if let Some(ui) = ui_weak.upgrade() {
+        ui.global::<AppState>().get_verify_after_write()
     } else {
-        return Err(anyhow::anyhow!("串口 {} 不存在", port_path));
+        return;
     };
 
-    let total_records = writable_records.len();
-    let mut processed = 0;
-
-    // 遍历所有可写寄存器记录
-    for record in writable_records {
-        // 更新状态文本
-        update_write_progress_status(
-            ui_weak,
-            processed,
-            total_records,
-            &record.page_addr,
-            &record.register,
+    config::get_runtime().spawn(async move {
+        let Some(pending) = PENDING_WRITE.lock().await.take() else {
+            return;
+        };
+        hide_write_diff(&ui_weak);
+
+        // 同一地址的多行一起写入，避免只写其中一行时覆盖其他位域
+        let changed_addresses: HashSet<u16> = pending
+            .diffs
+            .iter()
+            .filter(|diff| diff.will_change())
+            .flat_map(|diff| diff.record.def.addresses())
+            .collect();
+        let (changed, unchanged): (Vec<WriteDiff>, Vec<WriteDiff>) =
+            pending.diffs.into_iter().partition(|diff| {
+                diff.record
+                    .def
+                    .addresses()
+                    .iter()
+                    .any(|address| changed_addresses.contains(address))
+            });
+        CsvHandler::set_statuses(
+            unchanged
+                .iter()
+                .map(|diff| (RegisterKey::of(&diff.record), RowStatus::Unchanged))
+                .collect(),
         )
         .await;
 
-        // 解析页地址（从站地址）
-        let slave_address =
-            if record.page_addr.starts_with("0x") || record.page_addr.starts_with("0X") {
-                u16::from_str_radix(&record.page_addr[2..], 16)
-                    .map_err(|_| anyhow::anyhow!("无效的页地址格式: {}", record.page_addr))?
-            } else {
-                record
-                    .page_addr
-                    .parse::<u16>()
-                    .map_err(|_| anyhow::anyhow!("无效的页地址: {}", record.page_addr))?
-            };
+        let records = changed.into_iter().map(|diff| diff.record).collect();
+        run_device_write(ui_weak, pending.port_path, records, verify).await;
+    });
+}
 
-        // 解析要写入的值
-        let write_value = if record.value.starts_with("0x") || record.value.starts_with("0X") {
-            u16::from_str_radix(&record.value[2..], 16)
-                .map_err(|_| anyhow::anyhow!("无效的值格式: {}", record.value))?
-        } else {
-            record
-                .value
-                .parse::<u16>()
-                .map_err(|_| anyhow::anyhow!("无效的值: {}", record.value))?
-        };
+// 处理取消写入按钮点击事件
+fn handle_cancel_write_click(ui_weak: Weak<AppWindow>) {
+    config::get_runtime().spawn(async move {
+        if PENDING_WRITE.lock().await.take().is_none() {
+            return;
+        }
+        hide_write_diff(&ui_weak);
+        log::info!("取消写入，未向器件发送数据");
+        update_file_status(
+            &ui_weak,
+            "已取消写入，未向器件发送数据".to_string(),
+            slint::Color::from_rgb_u8(108, 117, 125), // 灰色
+        );
+    });
+}
 
-        match write_single_register(port_manager.clone(), 1, slave_address, write_value).await {
-            Ok(()) => {
-                log::info!(
-                    "成功写入 {}:{} = 0x{:04X}",
-                    record.page_addr,
-                    record.register,
-                    write_value
-                );
-            }
-            Err(e) => {
-                log::error!(
-                    "写入寄存器失败 {}:{} - {}",
-                    record.page_addr,
-                    record.register,
-                    e
+// 在表格中显示写入差异: 页地址、寄存器、当前值、写入值、是否修改
+fn show_write_diff(ui_weak: &Weak<AppWindow>, diffs: &[WriteDiff], summary: String) {
+    let rows: Vec<Vec<slint::SharedString>> = diffs
+        .iter()
+        .map(|diff| {
+            vec![
+                diff.record.page_addr.clone().into(),
+                diff.record.register.clone().into(),
+                diff.current
+                    .map_or("只写".to_string(), |value| {
+                        diff.record.def.format_value(value)
+                    })
+                    .into(),
+                diff.record.def.format_value(diff.target).into(),
+                if diff.will_change() {
+                    "修改"
+                } else {
+                    "不变"
+                }
+                .into(),
+            ]
+        })
+        .collect();
+
+    let ui_weak_clone = ui_weak.clone();
+    slint::invoke_from_event_loop(move || {
+        if let Some(ui) = ui_weak_clone.upgrade() {
+            ui.global::<AppState>()
+                .set_diff_table_data(to_table_model(rows));
+            ui.global::<AppState>().set_preview_summary(summary.into());
+            ui.global::<AppState>().set_preview_pending(true);
+        }
+    })
+    .unwrap();
+}
+
+// 表格行转换为 StandardTableView 的数据模型，需在UI线程中调用
+fn to_table_model(
+    rows: Vec<Vec<slint::SharedString>>,
+) -> slint::ModelRc<slint::ModelRc<slint::StandardListViewItem>> {
+    let table_model = slint::VecModel::from(
+        rows.into_iter()
+            .map(|row| {
+                let row_model = slint::VecModel::from(
+                    row.into_iter()
+                        .map(slint::StandardListViewItem::from)
+                        .collect::<Vec<_>>(),
                 );
-                return Err(anyhow::anyhow!(e));
-            }
+                slint::ModelRc::new(row_model)
+            })
+            .collect::<Vec<_>>(),
+    );
+    slint::ModelRc::new(table_model)
+}
+
+// 隐藏写入差异，表格恢复显示寄存器表
+fn hide_write_diff(ui_weak: &Weak<AppWindow>) {
+    let ui_weak_clone = ui_weak.clone();
+    let _ = slint::invoke_from_event_loop(move || {
+        if let Some(ui) = ui_weak_clone.upgrade() {
+            ui.global::<AppState>().set_preview_pending(false);
+        }
+    });
+}
+
+// 更新文件状态栏的文本和颜色
+fn update_file_status(ui_weak: &Weak<AppWindow>, status: String, color: slint::Color) {
+    let ui_weak_clone = ui_weak.clone();
+    let _ = slint::invoke_from_event_loop(move || {
+        if let Some(ui) = ui_weak_clone.upgrade() {
+            ui.global::<AppState>().set_file_status(status.into());
+            ui.global::<AppState>()
+                .set_file_status_color(slint::Brush::from(color));
         }
-        // 添加小延时避免过于频繁的通信
-        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
-        processed += 1;
+    });
+}
+
+// 执行器件寄存器写入操作
+// 每写完一块立即在表格中标记为已写入，停止或出错时其余行标记为未完成；
+// verify 为 true 时全部写完后回读校验，表格中标记校验结果；
+// 返回合计的重试次数和校验汇总
+async fn write_device_registers(
+    ui_weak: &Weak<AppWindow>,
+    port_path: &str,
+    job: &Job,
+    all_records: &[RegisterRecord],
+    verify: bool,
+) -> anyhow::Result<(u32, Option<VerifySummary>)> {
+    if all_records.is_empty() {
+        return Err(anyhow::anyhow!("没有找到寄存器数据，请先读取文件"));
     }
 
-    Ok(())
+    let registry = SerialPortRegistry::get_global().await;
+    let port_manager = get_open_transport(port_path, Priority::Bulk).await?;
+    let policy = registry.retry_policy();
+
+    // 写入期间暂停IO轮询，守卫在函数返回时释放
+    let _polling_pause = registry
+        .get_scheduler(port_path)
+        .await
+        .map(|scheduler| scheduler.pause_polling());
+
+    let writable: Vec<RegisterRecord> = all_records
+        .iter()
+        .filter(|record| device_io::is_writable(record))
+        .cloned()
+        .collect();
+    CsvHandler::set_status(&writable, RowStatus::Pending).await;
+    update_table_data_after_read(ui_weak).await?;
+
+    let (block_sender, blocks) = tokio::sync::mpsc::unbounded_channel();
+    let written = device_io::write_device_registers(
+        port_manager.clone(),
+        all_records,
+        &policy,
+        job.cancel_token(),
+        |index, total, block| update_write_progress_status(ui_weak, index, total, block),
+        move |block| {
+            let _ = block_sender.send(block.to_vec());
+        },
+    );
+    let applied = apply_completed_blocks(ui_weak, blocks, |block| async move {
+        CsvHandler::set_status(&block, RowStatus::Written).await;
+        Ok(())
+    });
+    let (written, finished) = tokio::join!(written, applied);
+
+    CsvHandler::mark_pending_unfinished().await;
+    update_table_data_after_read(ui_weak).await?;
+
+    let finished = finished?;
+    let retries = written?.retries;
+    if !verify {
+        return Ok((retries, None));
+    }
+
+    // 回读校验，停止时未校验的行保持已写入
+    let (block_sender, blocks) = tokio::sync::mpsc::unbounded_channel();
+    let verified = device_io::verify_device_registers(
+        port_manager,
+        &finished,
+        &policy,
+        job.cancel_token(),
+        |index, total, block| update_verify_progress_status(ui_weak, index, total, block),
+        move |block| {
+            let _ = block_sender.send(block.to_vec());
+        },
+    );
+    let applied = apply_completed_blocks(ui_weak, blocks, |block| async move {
+        CsvHandler::set_statuses(block.iter().map(verify_row_status).collect()).await;
+        Ok(())
+    });
+    let (verified, applied) = tokio::join!(verified, applied);
+
+    applied?;
+    let verified = verified?;
+    Ok((
+        retries + verified.retries,
+        Some(VerifySummary::from_results(&verified.value)),
+    ))
+}
+
+// 校验结果对应的表格行状态
+fn verify_row_status(result: &VerifyResult) -> (RegisterKey, RowStatus) {
+    let status = match result.outcome {
+        VerifyOutcome::Match => RowStatus::Verified,
+        VerifyOutcome::Mismatch { actual, .. } => RowStatus::Mismatch { actual },
+        VerifyOutcome::Failed(_) => RowStatus::VerifyFailed,
+        VerifyOutcome::NotReadable => RowStatus::NotVerifiable,
+    };
+    (RegisterKey::of(&result.record), status)
+}
+
+// 更新回读校验进度状态
+fn update_verify_progress_status(
+    ui_weak: &Weak<AppWindow>,
+    processed: usize,
+    total: usize,
+    current_block: &str,
+) {
+    let ui_weak_clone = ui_weak.clone();
+    let status_text = format!(
+        "正在回读校验... ({}/{}): {}",
+        processed + 1,
+        total,
+        current_block
+    );
+
+    slint::invoke_from_event_loop(move || {
+        if let Some(ui) = ui_weak_clone.upgrade() {
+            ui.global::<AppState>().set_file_status(status_text.into());
+            ui.global::<AppState>()
+                .set_file_status_color(slint::Brush::from(slint::Color::from_rgb_u8(23, 162, 184))); // 蓝色
+        }
+    })
+    .unwrap();
 }
 
 // 更新写入进度状态
-async fn update_write_progress_status(
+fn update_write_progress_status(
     ui_weak: &Weak<AppWindow>,
     processed: usize,
     total: usize,
-    page_addr: &str,
-    register: &str,
+    current_block: &str,
 ) {
     let ui_weak_clone = ui_weak.clone();
     let status_text = format!(
-        "正在写入器件数据... ({}/{}): {}:{}",
+        "正在写入器件数据... ({}/{}): {}",
         processed + 1,
         total,
-        page_addr,
-        register
+        current_block
     );
 
     slint::invoke_from_event_loop(move || {
@@ -1146,15 +2001,28 @@ async fn update_write_progress_status(
 }
 
 // 更新器件写入UI状态 - 成功
-async fn update_device_write_ui_success(ui_weak: &Weak<AppWindow>) {
+// 有校验汇总时一并显示，存在不一致或回读失败的寄存器时显示为红色
+async fn update_device_write_ui_success(
+    ui_weak: &Weak<AppWindow>,
+    retries: u32,
+    summary: Option<VerifySummary>,
+) {
     let ui_weak_clone = ui_weak.clone();
+    let status_text = match &summary {
+        Some(summary) => format!("器件写入完成，{}{}", summary, retries_note(retries)),
+        None => format!("器件写入完成{}", retries_note(retries)),
+    };
+    let color = if summary.is_none_or(|summary| summary.all_verified()) {
+        slint::Color::from_rgb_u8(40, 167, 69) // 绿色
+    } else {
+        slint::Color::from_rgb_u8(220, 53, 69) // 红色
+    };
 
     slint::invoke_from_event_loop(move || {
         if let Some(ui) = ui_weak_clone.upgrade() {
+            ui.global::<AppState>().set_file_status(status_text.into());
             ui.global::<AppState>()
-                .set_file_status("器件写入完成".into());
-            ui.global::<AppState>()
-                .set_file_status_color(slint::Brush::from(slint::Color::from_rgb_u8(40, 167, 69))); // 绿色
+                .set_file_status_color(slint::Brush::from(color));
         }
     })
     .unwrap();